@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An asset overridden by multiple enabled mods
+ */
+export type AssetConflict = { 
+/**
+ * e.g. `mod/resource/ui/menus/main.menu`, `rpak:common.rpak` or `audio:EventName`
+ */
+asset: string, 
+/**
+ * Mods overriding the asset in load order
+ */
+mods: Array<string>, 
+/**
+ * Mod whose version of the asset is used
+ */
+winner: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Version of a single core mod
+ */
+export type CoreModVersion = { name: string, version: string | null, };
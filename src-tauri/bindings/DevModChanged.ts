@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Sent whenever files of a linked mod change
+ */
+export type DevModChanged = { mod_name: string, 
+/**
+ * Changed, added or removed files relative to the mod folder
+ */
+changed_files: Array<string>, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A local mod source folder linked into a profile
+ */
+export type DevModLink = { mod_name: string, 
+/**
+ * Folder the mod is developed in
+ */
+source_path: string, 
+/**
+ * Mod folder inside the profile
+ */
+target_path: string, 
+/**
+ * Package folder generated for the mod, if linked into `packages/`
+ */
+package_path: string | null, 
+/**
+ * Whether the mod had to be copied as symlinks aren't allowed
+ */
+is_copy: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Size in bytes of a single named item, e.g. a folder, package or mod
+ */
+export type DiskUsageEntry = { name: string, size: bigint, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result of comparing the game files against the manifest
+ */
+export type GameFileVerificationResult = { missing_files: Array<string>, corrupt_files: Array<string>, 
+/**
+ * Files in game folders that are not part of the game nor installed by Northstar
+ */
+unexpected_files: Array<string>, 
+/**
+ * How to get the store the game was installed from to repair it
+ */
+repair_instructions: string, 
+/**
+ * Link opening the store's repair function directly, if there is one
+ */
+repair_url: string | null, };
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Everything an install is about to do
+ * Paths are relative to the game folder
+ */
+export type InstallPlan = { 
+/**
+ * Packages that will be downloaded, in install order
+ */
+packages: Array<string>, 
+/**
+ * Packages pulled in as dependencies of the requested package
+ */
+dependencies: Array<string>, 
+/**
+ * Files and folders that will be removed
+ */
+paths_to_delete: Array<string>, 
+/**
+ * Files and folders that will be written
+ */
+paths_to_write: Array<string>, 
+/**
+ * Total size of all downloads in bytes
+ */
+download_size: bigint, 
+/**
+ * Things to point out before confirming, e.g. warnings an actual install would show
+ */
+warnings: Array<string>, };
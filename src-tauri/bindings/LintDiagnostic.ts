@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LintSeverity } from "./LintSeverity";
+
+/**
+ * A single problem found in a mod
+ */
+export type LintDiagnostic = { 
+/**
+ * File the problem was found in
+ */
+file: string, 
+/**
+ * 1-based line in the file, if known
+ */
+line: number | null, severity: LintSeverity, message: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A ConVar exposed by a mod together with its current value
+ */
+export type ModConVar = { mod_name: string, name: string, default_value: string, current_value: string, help_string: string | null, flags: string | null, is_overridden: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A named snapshot of which mods are enabled
+ */
+export type ModPreset = { name: string, enabled_mods: { [key in string]?: boolean }, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Mods whose enabled status was changed by a dependency-aware toggle
+ */
+export type ModToggleResult = { enabled_mods: Array<string>, disabled_mods: Array<string>, 
+/**
+ * Enabled mods that depend on a disabled mod but were left enabled
+ */
+affected_dependents: Array<string>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CoreModVersion } from "./CoreModVersion";
+
+/**
+ * Versions of the parts making up a Northstar install, `None` if a part is missing
+ */
+export type NorthstarComponentVersions = { launcher_version: string | null, dll_version: string | null, core_mod_versions: Array<CoreModVersion>, 
+/**
+ * Set if the parts are not all of the same version
+ */
+mismatch: boolean, };
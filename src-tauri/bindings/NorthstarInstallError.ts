@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PreflightError } from "./PreflightError";
+
+/**
+ * Why installing Northstar failed
+ */
+export type NorthstarInstallError = { "Preflight": Array<PreflightError> } | { "Other": string };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where and how to publish a package
+ */
+export type PackageUploadOptions = { 
+/**
+ * Thunderstore team the package is published under
+ */
+team: string, communities: Array<string>, categories: Array<string>, has_nsfw_content: boolean, 
+/**
+ * Defaults to Thunderstore if not set
+ */
+base_url: string | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of publishing a package
+ */
+export type PackageUploadResult = { 
+/**
+ * Full name of the published version, set if successful
+ */
+package_version: string | null, 
+/**
+ * Validation errors reported by Thunderstore
+ */
+errors: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result of comparing an installed package against its file manifest
+ */
+export type PackageVerificationResult = { thunderstore_mod_string: string, modified_files: Array<string>, missing_files: Array<string>, extra_files: Array<string>, };
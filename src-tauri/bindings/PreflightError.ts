@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Problem that would make installing Northstar fail
+ */
+export type PreflightError = { "InsufficientDiskSpace": { required: bigint, available: bigint, } } | { "NotWritable": { path: string, } } | { "ReadOnlyFile": { path: string, } } | { "LockedFile": { path: string, } } | "NorthstarRunning";
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PreflightError } from "./PreflightError";
+
+/**
+ * Outcome of the checks run before installing Northstar
+ */
+export type PreflightReport = { 
+/**
+ * Problems that would make the install fail
+ */
+errors: Array<PreflightError>, 
+/**
+ * Whether files in use by other programs could be checked for
+ * Only Windows prevents replacing files that are in use, so this is false elsewhere
+ */
+lock_detection_available: boolean, };
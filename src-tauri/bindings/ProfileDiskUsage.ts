@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiskUsageEntry } from "./DiskUsageEntry";
+
+/**
+ * Breakdown of how much disk space a profile takes up
+ */
+export type ProfileDiskUsage = { profile: string, total_size: bigint, folders: Array<DiskUsageEntry>, packages: Array<DiskUsageEntry>, mods: Array<DiskUsageEntry>, };
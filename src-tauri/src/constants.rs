@@ -18,7 +18,7 @@ pub const CORE_MODS: [&str; 3] = [
     "Northstar.CustomServers",
 ];
 
-/// List of Thunderstoremods that shouldn't be installable
+/// List of Thunderstoremods that shouldn't be removable
 /// as they behave different than common Squirrel mods
 pub const BLACKLISTED_MODS: [&str; 3] = [
     "northstar-Northstar",
@@ -26,14 +26,39 @@ pub const BLACKLISTED_MODS: [&str; 3] = [
     "ebkr-r2modman",
 ];
 
-/// List of Thunderstoremods that have some specific install requirements that makes them different from standard mods
-pub const MODS_WITH_SPECIAL_REQUIREMENTS: [&str; 1] = ["NanohmProtogen-VanillaPlus"];
+/// URL of the latest rules describing special install behaviour of certain Thunderstore packages
+pub const INSTALL_RULES_URL: &str =
+    "https://raw.githubusercontent.com/R2NorthstarTools/FlightCore/main/src-tauri/install-rules.json";
+
+/// Base URL of Thunderstore used for publishing packages
+pub const THUNDERSTORE_BASE_URL: &str = "https://thunderstore.io";
+
+/// File inside an installed package listing the package's files and their hashes
+pub const PACKAGE_FILE_MANIFEST: &str = "flightcore-file-manifest.json";
+
+/// File inside a profile storing the user's ConVar overrides
+pub const CONVAR_OVERRIDES_FILE: &str = "flightcore-convars.json";
+
+/// File inside a profile storing the user's mod presets
+pub const MOD_PRESETS_FILE: &str = "flightcore-presets.json";
+
+/// File inside a profile remembering which mods were enabled before vanilla-compatible mode
+pub const VANILLA_MODE_SNAPSHOT_FILE: &str = "flightcore-vanilla-mode-snapshot.json";
+
+/// File inside a profile listing local mod source folders linked into it
+pub const DEV_MOD_LINKS_FILE: &str = "flightcore-dev-links.json";
+
+/// How often linked local mod source folders are checked for changes
+pub const DEV_MOD_WATCH_INTERVAL: Duration = Duration::from_millis(2000);
 
 /// Order in which the sections for release notes should be displayed
 pub const SECTION_ORDER: [&str; 11] = [
     "feat", "fix", "docs", "style", "refactor", "build", "test", "i18n", "ci", "chore", "other",
 ];
 
+/// How often a download is attempted before giving up if it fails verification
+pub const DOWNLOAD_ATTEMPTS: u32 = 3;
+
 /// Statistics (players and servers counts) refresh delay
 pub const REFRESH_DELAY: Duration = Duration::from_secs(5 * 60);
 
@@ -56,5 +81,23 @@ pub const NS_LAUNCHER_COMMITS_API_URL: &str =
 /// Filename of DLL that Northstar uses
 pub const NORTHSTAR_DLL: &str = "Northstar.dll";
 
+/// Folder inside the game folder keeping the previously installed Northstar files
+pub const NORTHSTAR_BACKUP_DIR: &str = "___flightcore-northstar-backup";
+
+/// Folder inside the game folder keeping the archive of the last installed Northstar release
+pub const NORTHSTAR_ARCHIVE_CACHE_DIR: &str = "___flightcore-northstar-cache";
+
+/// File in the game folder listing the files placed there by Northstar installs
+pub const NORTHSTAR_INSTALL_MANIFEST: &str = "flightcore-northstar-manifest.json";
+
 /// Profile that Northstar defaults to and ships with
 pub const NORTHSTAR_DEFAULT_PROFILE: &str = "R2Northstar";
+
+/// URL scheme of Thunderstore "Install with Mod Manager" links
+pub const DEEP_LINK_SCHEME: &str = "ror2mm";
+
+/// Local port on which a running FlightCore instance accepts links forwarded from new instances
+pub const DEEP_LINK_FORWARDING_PORT: u16 = 27415;
+
+/// How long either side of link forwarding waits for the other one
+pub const DEEP_LINK_FORWARDING_TIMEOUT: Duration = Duration::from_secs(5);
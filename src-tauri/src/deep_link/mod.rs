@@ -0,0 +1,207 @@
+//! Handling of Thunderstore "Install with Mod Manager" links
+//!
+//! Links have the form `ror2mm://v1/install/northstar.thunderstore.io/AUTHOR/MOD/VERSION/`
+use crate::constants::{DEEP_LINK_FORWARDING_PORT, DEEP_LINK_FORWARDING_TIMEOUT, DEEP_LINK_SCHEME};
+use crate::mod_management::ParsedThunderstoreModString;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::Mutex;
+use tauri::api::dialog::blocking::MessageDialogBuilder;
+use tauri::api::dialog::{MessageDialogButtons, MessageDialogKind};
+use tauri::Manager;
+
+/// Sent back by the running instance once it received a forwarded link
+const FORWARDING_ACKNOWLEDGEMENT: &str = "FlightCore received link";
+
+/// Links are short, so anything longer is not a link
+const MAX_FORWARDED_LINK_LENGTH: u64 = 4096;
+
+/// Holds a confirmed mod install request until the frontend picks it up
+#[derive(Default)]
+pub struct PendingDeepLink(Mutex<Option<String>>);
+
+/// Parses a `ror2mm://` link into the Thunderstore mod string it refers to
+pub fn parse_deep_link(url: &str) -> Result<ParsedThunderstoreModString, String> {
+    let path = url
+        .strip_prefix(&format!("{DEEP_LINK_SCHEME}://v1/install/"))
+        .ok_or_else(|| format!("Unsupported link \"{url}\""))?;
+
+    let parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    let [host, author_name, mod_name, version] = parts[..] else {
+        return Err(format!("Malformed link \"{url}\""));
+    };
+
+    // Only Northstar mods can be installed
+    if !host.eq_ignore_ascii_case("northstar.thunderstore.io") {
+        return Err(format!("\"{host}\" is not the Northstar Thunderstore"));
+    }
+
+    format!("{author_name}-{mod_name}-{version}")
+        .parse()
+        .map_err(|err: &str| err.to_string())
+}
+
+/// Returns the deep link FlightCore was started with, if any
+pub fn get_deep_link_from_args() -> Option<String> {
+    std::env::args().find(|arg| arg.starts_with(&format!("{DEEP_LINK_SCHEME}://")))
+}
+
+/// Passes the link on to an already running FlightCore instance
+/// Returns `false` if there is no instance that acknowledged the link
+pub fn forward_to_running_instance(url: &str) -> bool {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, DEEP_LINK_FORWARDING_PORT));
+    let acknowledgement = TcpStream::connect_timeout(&address, DEEP_LINK_FORWARDING_TIMEOUT)
+        .and_then(|mut stream| {
+            stream.set_read_timeout(Some(DEEP_LINK_FORWARDING_TIMEOUT))?;
+            stream.write_all(url.as_bytes())?;
+            // Closing our side marks the end of the link
+            stream.shutdown(Shutdown::Write)?;
+
+            let mut acknowledgement = String::new();
+            stream
+                .take(FORWARDING_ACKNOWLEDGEMENT.len() as u64)
+                .read_to_string(&mut acknowledgement)?;
+            Ok(acknowledgement)
+        });
+
+    match acknowledgement {
+        Ok(acknowledgement) if acknowledgement == FORWARDING_ACKNOWLEDGEMENT => {
+            log::info!("Forwarded {url} to running FlightCore instance");
+            true
+        }
+        Ok(_) => {
+            log::warn!("Port {DEEP_LINK_FORWARDING_PORT} is not used by FlightCore");
+            false
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::ConnectionRefused => false,
+        Err(err) => {
+            log::warn!("Failed forwarding link: {err}");
+            false
+        }
+    }
+}
+
+/// Reads a link forwarded by another instance and acknowledges it
+fn receive_forwarded_link(mut stream: TcpStream) -> std::io::Result<String> {
+    // Don't wait forever on clients that never finish sending
+    stream.set_read_timeout(Some(DEEP_LINK_FORWARDING_TIMEOUT))?;
+
+    let mut url = String::new();
+    (&mut stream)
+        .take(MAX_FORWARDED_LINK_LENGTH)
+        .read_to_string(&mut url)?;
+    stream.write_all(FORWARDING_ACKNOWLEDGEMENT.as_bytes())?;
+
+    Ok(url.trim().to_string())
+}
+
+/// Listens for links forwarded by other FlightCore instances
+pub fn listen_for_forwarded_links(app_handle: tauri::AppHandle) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, DEEP_LINK_FORWARDING_PORT)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            log::warn!("Unable to listen for forwarded links: {err}");
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            // Each link is handled separately so a slow client or open dialog doesn't block others
+            let app_handle = app_handle.clone();
+            std::thread::spawn(move || match stream.and_then(receive_forwarded_link) {
+                Ok(url) => handle_deep_link(&app_handle, &url),
+                Err(err) => log::warn!("Failed receiving forwarded link: {err}"),
+            });
+        }
+    });
+}
+
+/// Asks the user to confirm the install and hands the mod over to the frontend to install it
+/// into the active profile
+pub fn handle_deep_link(app_handle: &tauri::AppHandle, url: &str) {
+    log::info!("Handling deep link {url}");
+
+    let thunderstore_mod_string = match parse_deep_link(url) {
+        Ok(res) => res.to_string(),
+        Err(err) => {
+            log::warn!("{err}");
+            return;
+        }
+    };
+
+    let dialog = MessageDialogBuilder::new(
+        "Install mod",
+        format!("Do you want to install {thunderstore_mod_string} into the active profile?"),
+    )
+    .kind(MessageDialogKind::Info)
+    .buttons(MessageDialogButtons::OkCancel);
+
+    if !dialog.show() {
+        log::info!("Install of {thunderstore_mod_string} cancelled");
+        return;
+    }
+
+    *app_handle.state::<PendingDeepLink>().0.lock().unwrap() = Some(thunderstore_mod_string);
+    app_handle
+        .emit_all("deep-link-install-requested", ())
+        .unwrap();
+}
+
+/// Returns the mod string of a confirmed deep link install, if any, and clears it
+#[tauri::command]
+pub fn take_pending_deep_link(state: tauri::State<'_, PendingDeepLink>) -> Option<String> {
+    state.0.lock().unwrap().take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deep_link() {
+        let input = "ror2mm://v1/install/northstar.thunderstore.io/Author/Some_Mod/1.2.3/";
+        let output = parse_deep_link(input).unwrap().to_string();
+        assert_eq!(output, "Author-Some_Mod-1.2.3");
+    }
+
+    #[test]
+    fn test_parse_deep_link_without_trailing_slash() {
+        let input = "ror2mm://v1/install/northstar.thunderstore.io/Author/Some_Mod/1.2.3";
+        let output = parse_deep_link(input).unwrap().to_string();
+        assert_eq!(output, "Author-Some_Mod-1.2.3");
+    }
+
+    #[test]
+    fn test_parse_deep_link_other_community() {
+        let input = "ror2mm://v1/install/thunderstore.io/Author/Some_Mod/1.2.3/";
+        assert!(parse_deep_link(input).is_err());
+    }
+
+    #[test]
+    fn test_receive_forwarded_link() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let sender = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(b"ror2mm://v1/install/link").unwrap();
+            stream.shutdown(Shutdown::Write).unwrap();
+            let mut acknowledgement = String::new();
+            stream.read_to_string(&mut acknowledgement).unwrap();
+            acknowledgement
+        });
+
+        let (stream, _) = listener.accept().unwrap();
+        assert_eq!(
+            receive_forwarded_link(stream).unwrap(),
+            "ror2mm://v1/install/link"
+        );
+        assert_eq!(sender.join().unwrap(), FORWARDING_ACKNOWLEDGEMENT);
+    }
+
+    #[test]
+    fn test_parse_deep_link_malformed() {
+        let input = "ror2mm://v1/install/northstar.thunderstore.io/Author/1.2.3/";
+        assert!(parse_deep_link(input).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod mod_link;
+
 use crate::constants::NS_LAUNCHER_COMMITS_API_URL;
 use crate::github::{
     pull_requests::{check_github_api, download_zip_into_memory, get_launcher_download_link},
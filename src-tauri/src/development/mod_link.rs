@@ -0,0 +1,427 @@
+//! Linking mods that are developed outside the game folder into a profile
+//!
+//! Mods are symlinked where possible and copied otherwise. Linked source folders are watched
+//! for changes so that the frontend can offer restarting the game with the changes applied.
+use crate::constants::{DEV_MOD_LINKS_FILE, DEV_MOD_WATCH_INTERVAL};
+use crate::mod_management::ParsedThunderstoreModString;
+use crate::util::copy_dir_all;
+use crate::GameInstall;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::Manager;
+use ts_rs::TS;
+
+/// Author used for packages generated for linked mods
+const DEV_PACKAGE_AUTHOR: &str = "LocalDev";
+
+/// Target folders of links that currently have a watcher running
+static WATCHED_LINKS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// A local mod source folder linked into a profile
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct DevModLink {
+    mod_name: String,
+    /// Folder the mod is developed in
+    source_path: String,
+    /// Mod folder inside the profile
+    target_path: String,
+    /// Package folder generated for the mod, if linked into `packages/`
+    package_path: Option<String>,
+    /// Whether the mod had to be copied as symlinks aren't allowed
+    is_copy: bool,
+}
+
+/// Sent whenever files of a linked mod change
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+struct DevModChanged {
+    mod_name: String,
+    /// Changed, added or removed files relative to the mod folder
+    changed_files: Vec<String>,
+}
+
+/// The parts of `mod.json` needed to link a mod
+#[derive(Deserialize, Debug, Clone)]
+struct ModJson {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Version")]
+    version: Option<String>,
+}
+
+fn get_links_path(game_install: &GameInstall) -> String {
+    format!(
+        "{}/{}/{}",
+        game_install.game_path, game_install.profile, DEV_MOD_LINKS_FILE
+    )
+}
+
+fn read_links(game_install: &GameInstall) -> Result<Vec<DevModLink>, String> {
+    let links_path = get_links_path(game_install);
+
+    if !Path::new(&links_path).exists() {
+        return Ok(vec![]);
+    }
+
+    let data = std::fs::read_to_string(links_path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&data).map_err(|err| format!("Failed to read JSON due to: {}", err))
+}
+
+fn write_links(game_install: &GameInstall, links: &[DevModLink]) -> Result<(), String> {
+    std::fs::write(
+        get_links_path(game_install),
+        serde_json::to_string_pretty(links).unwrap(),
+    )
+    .map_err(|err| err.to_string())
+}
+
+/// Generates a package folder with a `manifest.json` for the mod and returns the package folder
+fn create_dev_package(game_install: &GameInstall, mod_json: &ModJson) -> Result<PathBuf, String> {
+    let package_name: String = mod_json
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    // Fall back to a placeholder version if the mod's one isn't valid for Thunderstore
+    let version = mod_json.version.clone().unwrap_or_default();
+    let mut thunderstore_mod_string = format!("{DEV_PACKAGE_AUTHOR}-{package_name}-{version}");
+    if thunderstore_mod_string
+        .parse::<ParsedThunderstoreModString>()
+        .is_err()
+    {
+        thunderstore_mod_string = format!("{DEV_PACKAGE_AUTHOR}-{package_name}-0.0.0");
+    }
+
+    let package_path = Path::new(&game_install.game_path)
+        .join(&game_install.profile)
+        .join("packages")
+        .join(&thunderstore_mod_string);
+    if package_path.exists() {
+        return Err(format!("{} already exists", package_path.display()));
+    }
+    std::fs::create_dir_all(package_path.join("mods")).map_err(|err| err.to_string())?;
+
+    let manifest = serde_json::json!({
+        "name": package_name,
+        "version_number": thunderstore_mod_string.rsplit('-').next().unwrap(),
+        "website_url": "",
+        "description": "Local development version",
+        "dependencies": [],
+    });
+    std::fs::write(
+        package_path.join("manifest.json"),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .map_err(|err| err.to_string())?;
+
+    Ok(package_path)
+}
+
+/// Symlinks `source` to `target`, copying it instead if symlinks can't be created
+/// Returns whether the folder was copied
+fn link_mod_folder(source: &Path, target: &Path) -> Result<bool, String> {
+    // Doesn't follow symlinks, so that broken links are detected too
+    if let Ok(metadata) = target.symlink_metadata() {
+        // Links left behind by an earlier session whose source folder is gone are replaced
+        if !metadata.file_type().is_symlink() || target.exists() {
+            return Err(format!("{} already exists", target.display()));
+        }
+        log::info!("Replacing broken link {}", target.display());
+        std::fs::remove_dir_all(target).map_err(|err| err.to_string())?;
+    }
+
+    match crate::platform_specific::create_dir_symlink(source, target) {
+        Ok(()) => Ok(false),
+        Err(err) => {
+            log::warn!("Failed creating symlink, copying mod instead: {err}");
+            copy_dir_all(source, target).map_err(|err| err.to_string())?;
+            Ok(true)
+        }
+    }
+}
+
+/// Links a local mod source folder into the profile's `mods/` folder,
+/// or into `packages/` with a generated manifest if `as_package` is set.
+/// Falls back to copying the folder if symlinks can't be created.
+#[tauri::command]
+pub fn link_dev_mod(
+    app_handle: tauri::AppHandle,
+    game_install: GameInstall,
+    source_path: String,
+    as_package: bool,
+) -> Result<DevModLink, String> {
+    let source = PathBuf::from(&source_path);
+    let data = std::fs::read_to_string(source.join("mod.json"))
+        .map_err(|_| format!("No mod.json found in {source_path}"))?;
+    let mod_json: ModJson =
+        json5::from_str(&data).map_err(|err| format!("Failed parsing mod.json with {}", err))?;
+
+    let mut links = read_links(&game_install)?;
+    if links.iter().any(|link| link.mod_name == mod_json.name) {
+        return Err(format!("{} is already linked", mod_json.name));
+    }
+
+    let folder_name = source
+        .file_name()
+        .ok_or_else(|| format!("Invalid mod folder {source_path}"))?;
+
+    let (mods_directory, package_path) = if as_package {
+        let package_path = create_dev_package(&game_install, &mod_json)?;
+        (package_path.join("mods"), Some(package_path))
+    } else {
+        let mods_directory = Path::new(&game_install.game_path)
+            .join(&game_install.profile)
+            .join("mods");
+        (mods_directory, None)
+    };
+
+    let target = mods_directory.join(folder_name);
+    let is_copy = link_mod_folder(&source, &target)?;
+
+    let link = DevModLink {
+        mod_name: mod_json.name,
+        source_path,
+        target_path: target.display().to_string(),
+        package_path: package_path.map(|path| path.display().to_string()),
+        is_copy,
+    };
+    log::info!("Linked {link:?}");
+
+    links.push(link.clone());
+    write_links(&game_install, &links)?;
+
+    watch_dev_mod(app_handle, link.clone());
+    Ok(link)
+}
+
+/// Removes a linked mod from the profile, leaving its source folder untouched
+#[tauri::command]
+pub fn unlink_dev_mod(game_install: GameInstall, mod_name: String) -> Result<(), String> {
+    let mut links = read_links(&game_install)?;
+    let position = links
+        .iter()
+        .position(|link| link.mod_name == mod_name)
+        .ok_or_else(|| format!("{mod_name} is not linked"))?;
+    let link = links.remove(position);
+
+    // Never follows the symlink, so the source folder stays intact
+    let path_to_remove = link.package_path.as_ref().unwrap_or(&link.target_path);
+    if Path::new(path_to_remove).symlink_metadata().is_ok() {
+        std::fs::remove_dir_all(path_to_remove).map_err(|err| err.to_string())?;
+    }
+
+    write_links(&game_install, &links)
+}
+
+/// Returns all mods linked into the given profile
+#[tauri::command]
+pub fn list_dev_mod_links(game_install: GameInstall) -> Result<Vec<DevModLink>, String> {
+    read_links(&game_install)
+}
+
+/// Starts watching all mods linked into the given profile for changes
+#[tauri::command]
+pub fn watch_dev_mod_links(
+    app_handle: tauri::AppHandle,
+    game_install: GameInstall,
+) -> Result<(), String> {
+    for link in read_links(&game_install)? {
+        watch_dev_mod(app_handle.clone(), link);
+    }
+    Ok(())
+}
+
+/// Adds last modification time and size of each file below `directory` to the snapshot
+/// Hidden files and folders like `.git` are skipped, they can be large and never affect the mod
+fn snapshot_directory(
+    root: &Path,
+    directory: &Path,
+    snapshot: &mut BTreeMap<PathBuf, (SystemTime, u64)>,
+) {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            snapshot_directory(root, &path, snapshot);
+        } else if let (Ok(relative_path), Ok(modified)) =
+            (path.strip_prefix(root), metadata.modified())
+        {
+            snapshot.insert(relative_path.to_path_buf(), (modified, metadata.len()));
+        }
+    }
+}
+
+/// Last modification time and size of each file in the folder
+fn snapshot_files(directory: &Path) -> BTreeMap<PathBuf, (SystemTime, u64)> {
+    let mut snapshot = BTreeMap::new();
+    snapshot_directory(directory, directory, &mut snapshot);
+    snapshot
+}
+
+/// Polls the source folder of the link for changes until the link is removed
+/// Copied mods are updated with the changes
+fn watch_dev_mod(app_handle: tauri::AppHandle, link: DevModLink) {
+    // Only a single watcher per link
+    if !WATCHED_LINKS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashSet::new)
+        .insert(link.target_path.clone())
+    {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let source = PathBuf::from(&link.source_path);
+        let target = PathBuf::from(&link.target_path);
+        let mut previous_snapshot = snapshot_files(&source);
+
+        loop {
+            std::thread::sleep(DEV_MOD_WATCH_INTERVAL);
+
+            // Link was removed
+            if target.symlink_metadata().is_err() {
+                break;
+            }
+
+            let snapshot = snapshot_files(&source);
+            let mut changed_files: Vec<String> = snapshot
+                .iter()
+                .filter(|(path, state)| previous_snapshot.get(*path) != Some(*state))
+                .chain(
+                    previous_snapshot
+                        .iter()
+                        .filter(|(path, _)| !snapshot.contains_key(*path)),
+                )
+                .map(|(path, _)| path.display().to_string())
+                .collect();
+            if changed_files.is_empty() {
+                continue;
+            }
+            changed_files.sort();
+            previous_snapshot = snapshot;
+
+            if link.is_copy {
+                let copy_result =
+                    std::fs::remove_dir_all(&target).and_then(|()| copy_dir_all(&source, &target));
+                if let Err(err) = copy_result {
+                    log::warn!("Failed copying changes of {}: {err}", link.mod_name);
+                }
+            }
+
+            log::info!("Files of {} changed: {changed_files:?}", link.mod_name);
+            app_handle
+                .emit_all(
+                    "dev-mod-changed",
+                    DevModChanged {
+                        mod_name: link.mod_name.clone(),
+                        changed_files,
+                    },
+                )
+                .unwrap();
+        }
+
+        if let Some(watched_links) = WATCHED_LINKS.lock().unwrap().as_mut() {
+            watched_links.remove(&link.target_path);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    #[test]
+    fn test_snapshot_files_skips_hidden_folders() {
+        let temp_directory = create_test_directory();
+        let directory = temp_directory.path();
+        std::fs::create_dir_all(directory.join("mod/scripts")).unwrap();
+        std::fs::create_dir_all(directory.join(".git/objects")).unwrap();
+        std::fs::write(directory.join("mod.json"), "{}").unwrap();
+        std::fs::write(directory.join("mod/scripts/main.nut"), "").unwrap();
+        std::fs::write(directory.join(".git/objects/object"), "").unwrap();
+
+        let snapshot = snapshot_files(directory);
+        assert_eq!(
+            snapshot.keys().collect::<Vec<_>>(),
+            vec![
+                &PathBuf::from("mod.json"),
+                &PathBuf::from("mod/scripts/main.nut")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_link_mod_folder_refuses_existing_folder() {
+        let temp_directory = create_test_directory();
+        let source = temp_directory.path().join("source");
+        let target = temp_directory.path().join("mods/Some.Mod");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+
+        assert!(link_mod_folder(&source, &target).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_link_mod_folder_replaces_broken_link() {
+        let temp_directory = create_test_directory();
+        let source = temp_directory.path().join("source");
+        let target = temp_directory.path().join("Some.Mod");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("mod.json"), "{}").unwrap();
+        // Left behind by an earlier session linking a folder that was removed since
+        std::os::unix::fs::symlink(temp_directory.path().join("removed"), &target).unwrap();
+
+        assert!(!link_mod_folder(&source, &target).unwrap());
+        assert!(target.join("mod.json").is_file());
+
+        // Working links are kept
+        assert!(link_mod_folder(&source, &target).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_unlink_dev_mod_removes_broken_link() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let source = game_path.join("source");
+        let target = game_path.join("R2Northstar/mods/Some.Mod");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(&source, &target).unwrap();
+
+        let game_install = GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        let link = DevModLink {
+            mod_name: "Some.Mod".to_string(),
+            source_path: source.display().to_string(),
+            target_path: target.display().to_string(),
+            package_path: None,
+            is_copy: false,
+        };
+        write_links(&game_install, &[link]).unwrap();
+        std::fs::remove_dir_all(&source).unwrap();
+
+        unlink_dev_mod(game_install.clone(), "Some.Mod".to_string()).unwrap();
+        assert!(target.symlink_metadata().is_err());
+        assert!(list_dev_mod_links(game_install).unwrap().is_empty());
+    }
+}
@@ -6,6 +6,7 @@
 use std::{env, time::Duration};
 
 mod constants;
+mod deep_link;
 mod development;
 mod github;
 mod mod_management;
@@ -47,6 +48,46 @@ fn main() {
     log::set_boxed_logger(Box::new(logger)).unwrap();
     log::set_max_level(log::LevelFilter::Info);
 
+    // Lint a mod from the command line instead of starting the GUI, e.g. `--lint path/to/mod`
+    if let Some(path) = std::env::args().skip_while(|arg| arg != "--lint").nth(1) {
+        platform_specific::attach_to_parent_console();
+        let diagnostics = mod_management::lint::lint(std::path::Path::new(&path));
+        for diagnostic in &diagnostics {
+            println!("{diagnostic}");
+        }
+        let has_errors = diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == mod_management::lint::LintSeverity::Error);
+        std::process::exit(i32::from(has_errors));
+    }
+
+    // Generate a manifest of a clean Titanfall 2 install for `verify_game_files`,
+    // e.g. `--generate-game-manifest path/to/Titanfall2 > titanfall2-file-manifest.json`
+    if let Some(path) = std::env::args()
+        .skip_while(|arg| arg != "--generate-game-manifest")
+        .nth(1)
+    {
+        platform_specific::attach_to_parent_console();
+        match repair_and_verify::game_files::generate_manifest(std::path::Path::new(&path)) {
+            Ok(manifest) => {
+                println!("{}", serde_json::to_string_pretty(&manifest).unwrap());
+                std::process::exit(0);
+            }
+            Err(err) => {
+                eprintln!("Failed generating manifest: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Hand deep links over to an already running instance instead of starting a second one
+    let deep_link = deep_link::get_deep_link_from_args();
+    if let Some(url) = &deep_link {
+        if deep_link::forward_to_running_instance(url) {
+            return;
+        }
+    }
+
     // Only enable Sentry crash logs on release
     #[cfg(not(debug_assertions))]
     let _guard = sentry::init((
@@ -60,7 +101,24 @@ fn main() {
 
     let tauri_builder_res = tauri::Builder::default()
         .plugin(tauri_plugin_store::Builder::default().build())
-        .setup(|app| {
+        .setup(move |app| {
+            // Links are only taken over once the user opted in through the settings
+            // Refresh the registration so it keeps pointing to this executable, unless the user
+            // has picked another handler since. Dev builds never touch it.
+            if !cfg!(debug_assertions) && platform_specific::is_deep_link_handler_registered() {
+                if let Err(err) = platform_specific::register_deep_link_handler() {
+                    log::warn!("Failed registering deep link handler: {err}");
+                }
+            }
+            deep_link::listen_for_forwarded_links(app.app_handle());
+
+            // Fetch latest special install rules for Thunderstore packages
+            tauri::async_runtime::spawn(mod_management::install_rules::load_install_rules());
+            if let Some(url) = deep_link {
+                let app_handle = app.app_handle();
+                std::thread::spawn(move || deep_link::handle_deep_link(&app_handle, &url));
+            }
+
             let app_handle = app.app_handle();
             tauri::async_runtime::spawn(async move {
                 loop {
@@ -108,8 +166,14 @@ fn main() {
             Ok(())
         })
         .manage(())
+        .manage(deep_link::PendingDeepLink::default())
         .invoke_handler(tauri::generate_handler![
+            deep_link::take_pending_deep_link,
             development::install_git_main,
+            development::mod_link::link_dev_mod,
+            development::mod_link::list_dev_mod_links,
+            development::mod_link::unlink_dev_mod,
+            development::mod_link::watch_dev_mod_links,
             github::compare_tags,
             github::get_list_of_tags,
             github::pull_requests::apply_launcher_pr,
@@ -120,26 +184,57 @@ fn main() {
             github::release_notes::generate_release_note_announcement,
             github::release_notes::get_newest_flightcore_version,
             github::release_notes::get_northstar_release_notes,
+            mod_management::conflicts::get_asset_conflicts,
+            mod_management::convars::get_mod_convars,
+            mod_management::convars::set_convar_override,
             mod_management::delete_northstar_mod,
             mod_management::delete_thunderstore_mod,
+            mod_management::dependencies::set_mod_enabled_status_with_dependencies,
             mod_management::get_installed_mods_and_properties,
+            mod_management::import::import_manual_mod,
             mod_management::install_mod_wrapper,
+            mod_management::lint::lint_mod,
+            mod_management::packaging::package_mod_folder,
+            mod_management::packaging::validate_mod_folder_for_packaging,
+            mod_management::plan::plan_mod_install,
+            mod_management::presets::apply_mod_preset,
+            mod_management::presets::delete_mod_preset,
+            mod_management::presets::list_mod_presets,
+            mod_management::presets::rename_mod_preset,
+            mod_management::presets::save_mod_preset,
             mod_management::set_mod_enabled_status,
+            mod_management::vanilla_mode::disable_vanilla_compatible_mode,
+            mod_management::vanilla_mode::enable_vanilla_compatible_mode,
+            mod_management::vanilla_mode::is_vanilla_compatible_mode_active,
+            mod_management::verify::reinstall_packages,
+            mod_management::verify::verify_installed_packages,
+            northstar::backup::get_northstar_backup_version,
+            northstar::backup::rollback_northstar,
             northstar::check_is_northstar_outdated,
             northstar::get_available_northstar_versions,
             northstar::get_northstar_version_number,
             northstar::install::find_game_install_location,
+            northstar::install::install_northstar_from_zip,
             northstar::install::install_northstar_wrapper,
+            northstar::install::plan_northstar_install,
             northstar::install::update_northstar,
             northstar::launch_northstar,
+            northstar::preflight::preflight_check_northstar_install,
             northstar::profile::clone_profile,
             northstar::profile::delete_profile,
             northstar::profile::fetch_profiles,
+            northstar::profile::get_all_profiles_disk_usage,
+            northstar::profile::get_profile_disk_usage,
             northstar::profile::validate_profile,
+            northstar::repair::repair_core_mods,
+            northstar::uninstall::uninstall_northstar,
+            northstar::version::get_northstar_component_versions,
             platform_specific::check_cgnat,
+            platform_specific::get_deep_link_handler_registered,
             platform_specific::get_host_os,
             platform_specific::get_local_northstar_proton_wrapper_version,
             platform_specific::install_northstar_proton_wrapper,
+            platform_specific::register_deep_link_handler_wrapper,
             platform_specific::uninstall_northstar_proton_wrapper,
             repair_and_verify::clean_up_download_folder_wrapper,
             repair_and_verify::disable_all_but_core,
@@ -147,6 +242,7 @@ fn main() {
             repair_and_verify::verify_game_files,
             repair_and_verify::verify_install_location,
             thunderstore::query_thunderstore_packages_api,
+            thunderstore::upload::upload_package_to_thunderstore,
             util::close_application,
             util::force_panic,
             util::get_flightcore_version_number,
@@ -0,0 +1,255 @@
+//! Finding assets that are overridden by more than one enabled mod
+//!
+//! Northstar loads mods ordered by `LoadPriority`, lowest first. When multiple mods override
+//! the same asset, the first one in load order is used. This mirrors `ModManager::LoadMods` in
+//! NorthstarLauncher, which sorts the loaded mods by `LoadPriority` and only registers a
+//! file override if no earlier mod registered the same path.
+//!
+//! Northstar's sort isn't stable, so which of several mods with the same `LoadPriority` wins
+//! isn't defined. Those are ordered by name here so that results are at least consistent.
+use crate::mod_management::get_installed_mods_and_properties;
+use crate::util::get_files_recursively;
+use crate::{GameInstall, NorthstarMod};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use ts_rs::TS;
+
+/// The parts of `mod.json` relevant for load order
+#[derive(Deserialize, Debug, Clone)]
+struct ModJsonLoadPriority {
+    #[serde(rename = "LoadPriority", default)]
+    load_priority: i32,
+}
+
+/// An asset overridden by multiple enabled mods
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct AssetConflict {
+    /// e.g. `mod/resource/ui/menus/main.menu`, `rpak:common.rpak` or `audio:EventName`
+    asset: String,
+    /// Mods overriding the asset in load order
+    mods: Vec<String>,
+    /// Mod whose version of the asset is used
+    winner: String,
+}
+
+fn get_load_priority(mod_directory: &Path) -> i32 {
+    std::fs::read_to_string(mod_directory.join("mod.json"))
+        .ok()
+        .and_then(|data| json5::from_str::<ModJsonLoadPriority>(&data).ok())
+        .map(|mod_json| mod_json.load_priority)
+        .unwrap_or_default()
+}
+
+/// Returns all assets the mod in the given folder overrides
+fn get_overridden_assets(mod_directory: &Path) -> Vec<String> {
+    let mut assets = Vec::new();
+
+    // Files under `mod/` replace the game's files of the same path
+    let files_directory = mod_directory.join("mod");
+    for file_path in get_files_recursively(&files_directory).unwrap_or_default() {
+        if let Ok(relative_path) = file_path.strip_prefix(&files_directory) {
+            let relative_path = relative_path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            assets.push(format!("mod/{}", relative_path.to_lowercase()));
+        }
+    }
+
+    // Rpaks declared in `paks/rpak.json`
+    if let Some(rpak_json) = std::fs::read_to_string(mod_directory.join("paks/rpak.json"))
+        .ok()
+        .and_then(|data| json5::from_str::<serde_json::Value>(&data).ok())
+    {
+        for section in ["Preload", "Postload", "Patch"] {
+            if let Some(section) = rpak_json.get(section).and_then(|value| value.as_object()) {
+                assets.extend(section.keys().map(|rpak| format!("rpak:{rpak}")));
+            }
+        }
+    }
+
+    // Audio events overridden by `audio/*.json`
+    if let Ok(entries) = std::fs::read_dir(mod_directory.join("audio")) {
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(audio_json) = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|data| json5::from_str::<serde_json::Value>(&data).ok())
+            else {
+                log::warn!("Failed parsing {}", path.display());
+                continue;
+            };
+            let event_ids = audio_json
+                .get("EventId")
+                .and_then(|value| value.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|event_id| event_id.as_str());
+            assets.extend(event_ids.map(|event_id| format!("audio:{event_id}")));
+        }
+    }
+
+    assets.sort();
+    assets.dedup();
+    assets
+}
+
+/// Finds assets overridden by more than one of the enabled mods
+fn find_asset_conflicts(installed_mods: Vec<NorthstarMod>) -> Vec<AssetConflict> {
+    let mut enabled_mods: Vec<(i32, String, String)> = installed_mods
+        .into_iter()
+        .filter(|ns_mod| ns_mod.enabled)
+        .map(|ns_mod| {
+            let load_priority = get_load_priority(Path::new(&ns_mod.directory));
+            (load_priority, ns_mod.name, ns_mod.directory)
+        })
+        .collect();
+    enabled_mods.sort();
+
+    let mut overriding_mods: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (_, mod_name, directory) in enabled_mods {
+        for asset in get_overridden_assets(Path::new(&directory)) {
+            overriding_mods
+                .entry(asset)
+                .or_default()
+                .push(mod_name.clone());
+        }
+    }
+
+    overriding_mods
+        .into_iter()
+        .filter(|(_, mods)| mods.len() > 1)
+        .map(|(asset, mods)| AssetConflict {
+            asset,
+            winner: mods[0].clone(),
+            mods,
+        })
+        .collect()
+}
+
+/// Returns all assets overridden by more than one enabled mod of the profile
+#[tauri::command]
+pub async fn get_asset_conflicts(game_install: GameInstall) -> Result<Vec<AssetConflict>, String> {
+    // Walks all mod files, so do it off the main thread
+    tauri::async_runtime::spawn_blocking(move || {
+        get_installed_mods_and_properties(game_install).map(find_asset_conflicts)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    /// Creates a mod with the given `LoadPriority` overriding the given files
+    fn create_mod(mods_directory: &Path, name: &str, load_priority: i32, files: &[&str]) -> String {
+        let mod_directory = mods_directory.join(name);
+        std::fs::create_dir_all(&mod_directory).unwrap();
+        std::fs::write(
+            mod_directory.join("mod.json"),
+            format!(r#"{{"Name": "{name}", "LoadPriority": {load_priority}}}"#),
+        )
+        .unwrap();
+        for file in files {
+            let path = mod_directory.join("mod").join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        mod_directory.to_string_lossy().to_string()
+    }
+
+    fn northstar_mod(name: &str, directory: String, enabled: bool) -> NorthstarMod {
+        NorthstarMod {
+            name: name.to_string(),
+            version: None,
+            thunderstore_mod_string: None,
+            enabled,
+            directory,
+        }
+    }
+
+    #[test]
+    fn test_find_asset_conflicts() {
+        let temp_directory = create_test_directory();
+        let mods_directory = temp_directory.path();
+        let menu = "resource/ui/menus/main.menu";
+        let installed_mods = vec![
+            northstar_mod(
+                "Late.Mod",
+                create_mod(mods_directory, "Late.Mod", 10, &[menu]),
+                true,
+            ),
+            northstar_mod(
+                "Early.Mod",
+                create_mod(
+                    mods_directory,
+                    "Early.Mod",
+                    1,
+                    &[menu, "scripts/unique.nut"],
+                ),
+                true,
+            ),
+        ];
+
+        let conflicts = find_asset_conflicts(installed_mods);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].asset, "mod/resource/ui/menus/main.menu");
+        // Lowest `LoadPriority` is loaded first and wins
+        assert_eq!(conflicts[0].mods, vec!["Early.Mod", "Late.Mod"]);
+        assert_eq!(conflicts[0].winner, "Early.Mod");
+    }
+
+    #[test]
+    fn test_find_asset_conflicts_load_priority_tie() {
+        let temp_directory = create_test_directory();
+        let mods_directory = temp_directory.path();
+        let installed_mods = vec![
+            northstar_mod(
+                "B.Mod",
+                create_mod(mods_directory, "B.Mod", 0, &["scripts/shared.nut"]),
+                true,
+            ),
+            northstar_mod(
+                "A.Mod",
+                create_mod(mods_directory, "A.Mod", 0, &["scripts/SHARED.nut"]),
+                true,
+            ),
+        ];
+
+        // Paths are compared case-insensitively and ties are ordered by name
+        let conflicts = find_asset_conflicts(installed_mods);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].mods, vec!["A.Mod", "B.Mod"]);
+        assert_eq!(conflicts[0].winner, "A.Mod");
+    }
+
+    #[test]
+    fn test_find_asset_conflicts_ignores_disabled_mods() {
+        let temp_directory = create_test_directory();
+        let mods_directory = temp_directory.path();
+        let installed_mods = vec![
+            northstar_mod(
+                "Enabled.Mod",
+                create_mod(mods_directory, "Enabled.Mod", 0, &["scripts/shared.nut"]),
+                true,
+            ),
+            northstar_mod(
+                "Disabled.Mod",
+                create_mod(mods_directory, "Disabled.Mod", 0, &["scripts/shared.nut"]),
+                false,
+            ),
+        ];
+
+        assert!(find_asset_conflicts(installed_mods).is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+//! Reading mod ConVars and persisting user overrides for them
+//!
+//! Overrides are stored per profile and passed to the game as launch arguments so that
+//! updating a mod doesn't reset them. `ns_startup_args.txt` is left alone as it is shared
+//! by all profiles and may contain arguments added by hand.
+use crate::constants::CONVAR_OVERRIDES_FILE;
+use crate::mod_management::get_installed_mods_and_properties;
+use crate::GameInstall;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// ConVar entry as found in `mod.json`
+#[derive(Deserialize, Debug, Clone)]
+struct ModJsonConVar {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "DefaultValue")]
+    default_value: serde_json::Value,
+    #[serde(rename = "HelpString")]
+    help_string: Option<String>,
+    #[serde(rename = "Flags")]
+    flags: Option<serde_json::Value>,
+}
+
+/// The parts of `mod.json` relevant for ConVars
+#[derive(Deserialize, Debug, Clone)]
+struct ModJsonConVars {
+    #[serde(rename = "ConVars", default)]
+    convars: Vec<ModJsonConVar>,
+}
+
+/// A ConVar exposed by a mod together with its current value
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct ModConVar {
+    mod_name: String,
+    name: String,
+    default_value: String,
+    current_value: String,
+    help_string: Option<String>,
+    flags: Option<String>,
+    is_overridden: bool,
+}
+
+/// Turns a JSON value that may be a string or a number into a string
+fn json_value_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Reads the ConVar overrides of the given profile
+fn read_convar_overrides(game_install: &GameInstall) -> Result<BTreeMap<String, String>, String> {
+    let overrides_path = format!(
+        "{}/{}/{}",
+        game_install.game_path, game_install.profile, CONVAR_OVERRIDES_FILE
+    );
+
+    if !std::path::Path::new(&overrides_path).exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let data = std::fs::read_to_string(overrides_path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&data).map_err(|err| format!("Failed to read JSON due to: {}", err))
+}
+
+/// Collects the ConVars declared by installed mods, optionally only by enabled ones
+fn get_declared_convars(
+    game_install: &GameInstall,
+    only_enabled: bool,
+) -> Result<Vec<(String, ModJsonConVar)>, String> {
+    let mut convars = Vec::new();
+    for ns_mod in get_installed_mods_and_properties(game_install.clone())? {
+        if only_enabled && !ns_mod.enabled {
+            continue;
+        }
+
+        let mod_json_path = format!("{}/mod.json", ns_mod.directory);
+        let data = match std::fs::read_to_string(&mod_json_path) {
+            Ok(data) => data,
+            Err(err) => {
+                log::warn!("Failed reading {}: {}", mod_json_path, err);
+                continue;
+            }
+        };
+        let parsed_mod_json: ModJsonConVars = match json5::from_str(&data) {
+            Ok(parsed_json) => parsed_json,
+            Err(err) => {
+                log::warn!("Failed parsing {} with {}", mod_json_path, err);
+                continue;
+            }
+        };
+
+        for convar in parsed_mod_json.convars {
+            convars.push((ns_mod.name.clone(), convar));
+        }
+    }
+    Ok(convars)
+}
+
+/// Returns the ConVars of all enabled mods with their default and current values
+#[tauri::command]
+pub fn get_mod_convars(game_install: GameInstall) -> Result<Vec<ModConVar>, String> {
+    let overrides = read_convar_overrides(&game_install)?;
+
+    let convars = get_declared_convars(&game_install, true)?
+        .into_iter()
+        .map(|(mod_name, convar)| {
+            let default_value = json_value_to_string(&convar.default_value);
+            let current_value = overrides.get(&convar.name).cloned();
+            ModConVar {
+                mod_name,
+                is_overridden: current_value.is_some(),
+                current_value: current_value.unwrap_or_else(|| default_value.clone()),
+                default_value,
+                help_string: convar.help_string,
+                flags: convar.flags.as_ref().map(json_value_to_string),
+                name: convar.name,
+            }
+        })
+        .collect();
+
+    Ok(convars)
+}
+
+/// Sets the value of a ConVar for the given profile
+/// Passing no value removes the override and restores the mod's default
+#[tauri::command]
+pub fn set_convar_override(
+    game_install: GameInstall,
+    name: String,
+    value: Option<String>,
+) -> Result<(), String> {
+    let mut overrides = read_convar_overrides(&game_install)?;
+
+    match value {
+        Some(value) => overrides.insert(name, value),
+        None => overrides.remove(&name),
+    };
+
+    let overrides_path = format!(
+        "{}/{}/{}",
+        game_install.game_path, game_install.profile, CONVAR_OVERRIDES_FILE
+    );
+    std::fs::write(
+        overrides_path,
+        serde_json::to_string_pretty(&overrides).unwrap(),
+    )
+    .map_err(|err| err.to_string())
+}
+
+/// Returns the ConVar overrides of the given profile as `+convar value` launch arguments
+pub fn get_convar_launch_args(game_install: &GameInstall) -> Vec<String> {
+    let overrides = match read_convar_overrides(game_install) {
+        Ok(overrides) => overrides,
+        Err(err) => {
+            log::warn!("Failed reading ConVar overrides: {err}");
+            return Vec::new();
+        }
+    };
+
+    overrides
+        .into_iter()
+        .flat_map(|(name, value)| [format!("+{name}"), value.replace('"', "")])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    #[test]
+    fn test_get_convar_launch_args() {
+        let temp_directory = create_test_directory();
+        let game_install = GameInstall {
+            game_path: temp_directory.path().to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        assert!(get_convar_launch_args(&game_install).is_empty());
+
+        std::fs::create_dir_all(temp_directory.path().join("R2Northstar")).unwrap();
+        set_convar_override(
+            game_install.clone(),
+            "some_convar".to_string(),
+            Some("-1".to_string()),
+        )
+        .unwrap();
+        set_convar_override(
+            game_install.clone(),
+            "other_convar".to_string(),
+            Some("\"hello world\"".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            get_convar_launch_args(&game_install),
+            vec!["+other_convar", "hello world", "+some_convar", "-1"]
+        );
+
+        // Removing an override restores the mod's default
+        set_convar_override(game_install.clone(), "other_convar".to_string(), None).unwrap();
+        assert_eq!(
+            get_convar_launch_args(&game_install),
+            vec!["+some_convar", "-1"]
+        );
+    }
+}
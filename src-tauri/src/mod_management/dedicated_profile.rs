@@ -0,0 +1,254 @@
+//! Installing packages that need their own profile and launch flow, e.g. VanillaPlus
+//!
+//! Such packages are installed in the legacy layout, i.e. their mods are extracted directly
+//! into the `mods` folder of the dedicated profile instead of the `packages` folder.
+use crate::constants::CORE_MODS;
+use crate::mod_management::{legacy, ParsedThunderstoreModString};
+use crate::GameInstall;
+use std::io::Read;
+use std::path::Path;
+
+/// Creates the dedicated profile if it doesn't exist yet
+/// The profile gets the Northstar DLL and core mods of the profile the install was started from
+pub fn ensure_dedicated_profile(game_install: &GameInstall, profile: &str) -> Result<(), String> {
+    let profile_path = Path::new(&game_install.game_path).join(profile);
+    if profile_path.is_dir() {
+        return Ok(());
+    }
+
+    log::info!("Creating dedicated profile {profile}");
+    let source_profile_path = Path::new(&game_install.game_path).join(&game_install.profile);
+
+    std::fs::create_dir_all(profile_path.join("mods")).map_err(|err| err.to_string())?;
+
+    let source_dll = source_profile_path.join("Northstar.dll");
+    if source_dll.exists() {
+        std::fs::copy(source_dll, profile_path.join("Northstar.dll"))
+            .map_err(|err| format!("Failed copying Northstar.dll: {err}"))?;
+    }
+
+    for core_mod in CORE_MODS {
+        let source_mod = source_profile_path.join("mods").join(core_mod);
+        if !source_mod.is_dir() {
+            log::warn!("Core mod {core_mod} not found in {}", game_install.profile);
+            continue;
+        }
+        crate::util::copy_dir_all(source_mod, profile_path.join("mods").join(core_mod))
+            .map_err(|err| format!("Failed copying {core_mod}: {err}"))?;
+    }
+
+    Ok(())
+}
+
+/// Extracts the mods of a package into the `mods` folder of the given profile
+/// and marks each of them with the package they are part of
+pub fn install_into_dedicated_profile(
+    thunderstore_mod_string: &str,
+    zip_file: &std::fs::File,
+    game_install: &GameInstall,
+) -> Result<(), String> {
+    let parsed_mod_string: ParsedThunderstoreModString = thunderstore_mod_string
+        .parse()
+        .map_err(|err: &str| err.to_string())?;
+
+    let mut archive = zip::read::ZipArchive::new(zip_file)
+        .map_err(|err| format!("Failed reading zip file: {err}"))?;
+
+    let mut manifest = String::new();
+    archive
+        .by_name("manifest.json")
+        .map_err(|_| "Package does not contain a manifest.json".to_string())?
+        .read_to_string(&mut manifest)
+        .map_err(|err| err.to_string())?;
+
+    // Remove any previously installed version of the package
+    legacy::delete_legacy_package_install(thunderstore_mod_string, game_install)?;
+
+    let mods_directory = Path::new(&game_install.game_path)
+        .join(&game_install.profile)
+        .join("mods");
+    let mut installed_mods: Vec<String> = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|err| err.to_string())?;
+        let Some(relative_path) = file
+            .enclosed_name()
+            .and_then(|path| path.strip_prefix("mods").ok().map(Path::to_path_buf))
+        else {
+            continue;
+        };
+        let Some(mod_folder) = relative_path.iter().next() else {
+            continue;
+        };
+        let mod_folder = mod_folder.to_string_lossy().to_string();
+        if !installed_mods.contains(&mod_folder) {
+            installed_mods.push(mod_folder);
+        }
+
+        let out = mods_directory.join(&relative_path);
+        if file.is_dir() {
+            std::fs::create_dir_all(&out).map_err(|err| err.to_string())?;
+            continue;
+        }
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+
+        let mut outfile = std::fs::File::create(&out).map_err(|err| err.to_string())?;
+        std::io::copy(&mut file, &mut outfile)
+            .map_err(|err| format!("Failed writing {}: {err}", out.display()))?;
+    }
+
+    if installed_mods.is_empty() {
+        return Err("Mod not correctly formatted".to_string());
+    }
+
+    for mod_folder in installed_mods {
+        let mod_path = mods_directory.join(&mod_folder);
+        std::fs::write(mod_path.join("manifest.json"), &manifest).map_err(|err| err.to_string())?;
+        std::fs::write(
+            mod_path.join("thunderstore_author.txt"),
+            &parsed_mod_string.author_name,
+        )
+        .map_err(|err| err.to_string())?;
+    }
+
+    log::info!(
+        "Installed {thunderstore_mod_string} into dedicated profile {}",
+        game_install.profile
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+    use crate::InstallType;
+    use std::io::Write;
+
+    fn create_game_install(game_path: &Path, profile: &str) -> GameInstall {
+        GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: profile.to_string(),
+            install_type: InstallType::UNKNOWN,
+        }
+    }
+
+    /// Package laid out like VanillaPlus on Thunderstore, with the given files inside its mod
+    fn create_package(path: &Path, version: &str, mod_files: &[&str]) -> std::fs::File {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.start_file("manifest.json", options).unwrap();
+        write!(
+            zip,
+            r#"{{"name": "VanillaPlus", "version_number": "{version}", "dependencies": []}}"#
+        )
+        .unwrap();
+        zip.start_file("icon.png", options).unwrap();
+        zip.start_file("mods/VanillaPlus/mod.json", options)
+            .unwrap();
+        zip.write_all(br#"{"Name": "VanillaPlus"}"#).unwrap();
+        for file in mod_files {
+            zip.start_file(format!("mods/VanillaPlus/{file}"), options)
+                .unwrap();
+        }
+        zip.finish().unwrap();
+        std::fs::File::open(path).unwrap()
+    }
+
+    #[test]
+    fn test_ensure_dedicated_profile() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let source_profile = game_path.join("R2Northstar");
+        std::fs::create_dir_all(source_profile.join("mods/Northstar.Client")).unwrap();
+        std::fs::write(source_profile.join("Northstar.dll"), "dll").unwrap();
+        std::fs::write(source_profile.join("mods/Northstar.Client/mod.json"), "{}").unwrap();
+        // Not a core mod, so it stays in the source profile
+        std::fs::create_dir_all(source_profile.join("mods/SomeMod")).unwrap();
+
+        let game_install = create_game_install(game_path, "R2Northstar");
+        ensure_dedicated_profile(&game_install, "R2VanillaPlus").unwrap();
+
+        let profile = game_path.join("R2VanillaPlus");
+        assert_eq!(
+            std::fs::read_to_string(profile.join("Northstar.dll")).unwrap(),
+            "dll"
+        );
+        assert!(profile.join("mods/Northstar.Client/mod.json").is_file());
+        assert!(!profile.join("mods/SomeMod").exists());
+
+        // An existing profile is left as it is
+        std::fs::remove_file(profile.join("Northstar.dll")).unwrap();
+        ensure_dedicated_profile(&game_install, "R2VanillaPlus").unwrap();
+        assert!(!profile.join("Northstar.dll").exists());
+    }
+
+    #[test]
+    fn test_install_into_dedicated_profile() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let mods_directory = game_path.join("R2VanillaPlus/mods");
+        std::fs::create_dir_all(&mods_directory).unwrap();
+        let game_install = create_game_install(game_path, "R2VanillaPlus");
+
+        let zip_file = create_package(&game_path.join("1.0.0.zip"), "1.0.0", &["old.nut"]);
+        install_into_dedicated_profile(
+            "NanohmProtogen-VanillaPlus-1.0.0",
+            &zip_file,
+            &game_install,
+        )
+        .unwrap();
+
+        let mod_directory = mods_directory.join("VanillaPlus");
+        assert!(mod_directory.join("mod.json").is_file());
+        assert!(mod_directory.join("old.nut").is_file());
+        assert_eq!(
+            std::fs::read_to_string(mod_directory.join("thunderstore_author.txt")).unwrap(),
+            "NanohmProtogen"
+        );
+        let manifest = std::fs::read_to_string(mod_directory.join("manifest.json")).unwrap();
+        assert!(manifest.contains("1.0.0"));
+        // Only the contents of `mods` are extracted
+        assert!(!mods_directory.join("icon.png").exists());
+
+        // Updating replaces the previous version entirely
+        let zip_file = create_package(&game_path.join("2.0.0.zip"), "2.0.0", &["new.nut"]);
+        install_into_dedicated_profile(
+            "NanohmProtogen-VanillaPlus-2.0.0",
+            &zip_file,
+            &game_install,
+        )
+        .unwrap();
+
+        assert!(mod_directory.join("new.nut").is_file());
+        assert!(!mod_directory.join("old.nut").exists());
+        let manifest = std::fs::read_to_string(mod_directory.join("manifest.json")).unwrap();
+        assert!(manifest.contains("2.0.0"));
+    }
+
+    #[test]
+    fn test_install_into_dedicated_profile_without_mods() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        std::fs::create_dir_all(game_path.join("R2VanillaPlus/mods")).unwrap();
+        let game_install = create_game_install(game_path, "R2VanillaPlus");
+
+        let path = game_path.join("empty.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        zip.start_file("manifest.json", zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(br#"{"name": "VanillaPlus", "version_number": "1.0.0"}"#)
+            .unwrap();
+        zip.finish().unwrap();
+
+        let zip_file = std::fs::File::open(&path).unwrap();
+        assert!(install_into_dedicated_profile(
+            "NanohmProtogen-VanillaPlus-1.0.0",
+            &zip_file,
+            &game_install
+        )
+        .is_err());
+    }
+}
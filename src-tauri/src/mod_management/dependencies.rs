@@ -0,0 +1,302 @@
+//! Enabling and disabling mods together with their dependencies and dependents
+
+use crate::mod_management::{
+    get_installed_mods_and_properties, write_enabled_mods_json, ParsedThunderstoreModString,
+    ThunderstoreManifest,
+};
+use crate::{GameInstall, NorthstarMod};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use ts_rs::TS;
+
+/// The parts of `mod.json` relevant for dependencies
+#[derive(Deserialize, Debug, Clone)]
+struct ModJsonDependencies {
+    /// Maps constant name to name of the mod depended on
+    #[serde(rename = "Dependencies", default)]
+    dependencies: BTreeMap<String, String>,
+}
+
+/// Mods whose enabled status was changed by a dependency-aware toggle
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct ModToggleResult {
+    enabled_mods: Vec<String>,
+    disabled_mods: Vec<String>,
+    /// Enabled mods that depend on a disabled mod but were left enabled
+    affected_dependents: Vec<String>,
+}
+
+/// Reads the Thunderstore manifest of the package the given mod was installed from
+fn read_package_manifest(ns_mod: &NorthstarMod) -> Option<ThunderstoreManifest> {
+    let mod_directory = Path::new(&ns_mod.directory);
+    // Packages keep their manifest two levels up (`packages/PACKAGE/mods/MOD`),
+    // legacy installs keep it inside the mod folder
+    let candidates = [
+        mod_directory.join("../../manifest.json"),
+        mod_directory.join("manifest.json"),
+    ];
+
+    candidates.iter().find_map(|manifest_path| {
+        let data = std::fs::read_to_string(manifest_path).ok()?;
+        json5::from_str(&data).ok()
+    })
+}
+
+/// Builds a map from mod name to the names of the mods it depends on
+fn build_dependency_graph(installed_mods: &[NorthstarMod]) -> HashMap<String, BTreeSet<String>> {
+    // Which mods a Thunderstore package (`AUTHOR-MOD`) consists of
+    let mut mods_by_package: HashMap<String, Vec<String>> = HashMap::new();
+    for ns_mod in installed_mods {
+        if let Some(parsed) = ns_mod
+            .thunderstore_mod_string
+            .as_ref()
+            .and_then(|ts_string| ts_string.parse::<ParsedThunderstoreModString>().ok())
+        {
+            mods_by_package
+                .entry(format!("{}-{}", parsed.author_name, parsed.mod_name))
+                .or_default()
+                .push(ns_mod.name.clone());
+        }
+    }
+
+    let mut graph: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for ns_mod in installed_mods {
+        let dependencies = graph.entry(ns_mod.name.clone()).or_default();
+
+        // `Dependencies` from `mod.json`
+        if let Ok(data) = std::fs::read_to_string(format!("{}/mod.json", ns_mod.directory)) {
+            match json5::from_str::<ModJsonDependencies>(&data) {
+                Ok(parsed_mod_json) => {
+                    dependencies.extend(parsed_mod_json.dependencies.into_values())
+                }
+                Err(err) => log::warn!("Failed parsing mod.json of {}: {}", ns_mod.name, err),
+            }
+        }
+
+        // `dependencies` from the package's `manifest.json`
+        if let Some(manifest) = read_package_manifest(ns_mod) {
+            for dependency in manifest.dependencies {
+                let Ok(parsed) = dependency.parse::<ParsedThunderstoreModString>() else {
+                    continue;
+                };
+                if let Some(package_mods) =
+                    mods_by_package.get(&format!("{}-{}", parsed.author_name, parsed.mod_name))
+                {
+                    dependencies.extend(package_mods.iter().cloned());
+                }
+            }
+        }
+
+        // A mod never depends on itself
+        dependencies.remove(&ns_mod.name);
+    }
+
+    graph
+}
+
+/// Reverses the dependency graph to map each mod to the mods depending on it
+fn build_reverse_graph(
+    graph: &HashMap<String, BTreeSet<String>>,
+) -> HashMap<String, BTreeSet<String>> {
+    let mut reverse_graph: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for (dependent, dependencies) in graph {
+        for dependency in dependencies {
+            reverse_graph
+                .entry(dependency.clone())
+                .or_default()
+                .insert(dependent.clone());
+        }
+    }
+    reverse_graph
+}
+
+/// Collects all mods transitively reachable from `start` in the given graph, excluding `start`
+fn collect_transitive(graph: &HashMap<String, BTreeSet<String>>, start: &str) -> BTreeSet<String> {
+    let mut found = BTreeSet::new();
+    let mut to_visit = vec![start.to_string()];
+
+    while let Some(current) = to_visit.pop() {
+        for next in graph.get(&current).into_iter().flatten() {
+            if next != start && found.insert(next.clone()) {
+                to_visit.push(next.clone());
+            }
+        }
+    }
+
+    found
+}
+
+/// Sets the status of a mod to enabled/disabled taking dependencies into account
+/// - Enabling a mod also enables everything it depends on
+/// - Disabling a mod disables its dependents if `cascade` is set,
+///   otherwise they are left enabled and reported back
+#[tauri::command]
+pub fn set_mod_enabled_status_with_dependencies(
+    game_install: GameInstall,
+    mod_name: String,
+    is_enabled: bool,
+    cascade: bool,
+) -> Result<ModToggleResult, String> {
+    let installed_mods = get_installed_mods_and_properties(game_install.clone())?;
+    if !installed_mods.iter().any(|ns_mod| ns_mod.name == mod_name) {
+        return Err(format!("Mod {mod_name} not found to be installed"));
+    }
+
+    let graph = build_dependency_graph(&installed_mods);
+
+    let mut enabled_status: BTreeMap<String, bool> = installed_mods
+        .iter()
+        .map(|ns_mod| (ns_mod.name.clone(), ns_mod.enabled))
+        .collect();
+
+    let mut to_change = BTreeSet::from([mod_name.clone()]);
+    let mut affected_dependents = Vec::new();
+
+    if is_enabled {
+        to_change.extend(collect_transitive(&graph, &mod_name));
+    } else {
+        let dependents = collect_transitive(&build_reverse_graph(&graph), &mod_name)
+            .into_iter()
+            .filter(|dependent| enabled_status.get(dependent).copied().unwrap_or(false));
+
+        if cascade {
+            to_change.extend(dependents);
+        } else {
+            affected_dependents.extend(dependents);
+        }
+    }
+
+    let mut enabled_mods = Vec::new();
+    let mut disabled_mods = Vec::new();
+    for name in to_change {
+        // Dependencies that aren't installed can't be toggled
+        let Some(current_status) = enabled_status.get_mut(&name) else {
+            continue;
+        };
+        if *current_status == is_enabled {
+            continue;
+        }
+
+        *current_status = is_enabled;
+        if is_enabled {
+            enabled_mods.push(name);
+        } else {
+            disabled_mods.push(name);
+        }
+    }
+
+    let mapping = enabled_status
+        .into_iter()
+        .map(|(name, enabled)| (name, serde_json::Value::Bool(enabled)))
+        .collect();
+    write_enabled_mods_json(&game_install, &serde_json::Value::Object(mapping))?;
+
+    Ok(ModToggleResult {
+        enabled_mods,
+        disabled_mods,
+        affected_dependents,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    fn graph_from(edges: &[(&str, &[&str])]) -> HashMap<String, BTreeSet<String>> {
+        edges
+            .iter()
+            .map(|(name, dependencies)| {
+                (
+                    name.to_string(),
+                    dependencies.iter().map(|d| d.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_collect_transitive() {
+        let graph = graph_from(&[("A", &["B"]), ("B", &["C", "D"]), ("C", &[]), ("E", &["A"])]);
+        assert_eq!(collect_transitive(&graph, "A"), names(&["B", "C", "D"]));
+        assert_eq!(collect_transitive(&graph, "C"), names(&[]));
+        // Mods missing from the graph have no dependencies
+        assert_eq!(collect_transitive(&graph, "Missing"), names(&[]));
+
+        let reverse_graph = build_reverse_graph(&graph);
+        assert_eq!(
+            collect_transitive(&reverse_graph, "C"),
+            names(&["A", "B", "E"])
+        );
+    }
+
+    #[test]
+    fn test_collect_transitive_with_cycle() {
+        let graph = graph_from(&[("A", &["B"]), ("B", &["C"]), ("C", &["A", "D"])]);
+        // Terminates and never includes the starting mod
+        assert_eq!(collect_transitive(&graph, "A"), names(&["B", "C", "D"]));
+        assert_eq!(
+            collect_transitive(&build_reverse_graph(&graph), "D"),
+            names(&["A", "B", "C"])
+        );
+    }
+
+    #[test]
+    fn test_build_dependency_graph() {
+        let packages_directory = create_test_directory();
+        let library_directory = packages_directory.path().join("Author-Library-1.0.0");
+        let user_directory = packages_directory.path().join("Author-User-1.0.0");
+        std::fs::create_dir_all(library_directory.join("mods/Author.Library")).unwrap();
+        std::fs::create_dir_all(user_directory.join("mods/Author.User")).unwrap();
+        std::fs::write(
+            library_directory.join("manifest.json"),
+            r#"{"name": "Library", "version_number": "1.0.0"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            user_directory.join("manifest.json"),
+            r#"{"name": "User", "version_number": "1.0.0", "dependencies": ["Author-Library-1.0.0"]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            user_directory.join("mods/Author.User/mod.json"),
+            r#"{"Name": "Author.User", "Dependencies": {"HAS_SELF": "Author.User", "HAS_CLIENT": "Northstar.Client"}}"#,
+        )
+        .unwrap();
+
+        let installed_mods = [
+            NorthstarMod {
+                name: "Author.Library".to_string(),
+                version: Some("1.0.0".to_string()),
+                thunderstore_mod_string: Some("Author-Library-1.0.0".to_string()),
+                enabled: true,
+                directory: library_directory
+                    .join("mods/Author.Library")
+                    .to_string_lossy()
+                    .to_string(),
+            },
+            NorthstarMod {
+                name: "Author.User".to_string(),
+                version: Some("1.0.0".to_string()),
+                thunderstore_mod_string: Some("Author-User-1.0.0".to_string()),
+                enabled: true,
+                directory: user_directory
+                    .join("mods/Author.User")
+                    .to_string_lossy()
+                    .to_string(),
+            },
+        ];
+        let graph = build_dependency_graph(&installed_mods);
+        assert_eq!(graph["Author.Library"], names(&[]));
+        assert_eq!(
+            graph["Author.User"],
+            names(&["Author.Library", "Northstar.Client"])
+        );
+    }
+}
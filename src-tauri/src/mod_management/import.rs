@@ -0,0 +1,343 @@
+//! Turning mods manually dropped into the `mods` folder into tracked Thunderstore packages
+use crate::mod_management::verify::{hash_package_files, write_package_file_manifest};
+use crate::mod_management::{fc_sanity_check, legacy, ParsedThunderstoreModString, TempFile};
+use crate::util::{list_remote_zip_entries, ZipEntryInfo};
+use crate::GameInstall;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Normalizes a mod or package name for comparison, e.g. `Author.Some_Mod` -> `authorsomemod`
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Derives the Thunderstore mod string from a package download URL
+/// e.g. `https://thunderstore.io/package/download/AUTHOR/MOD/VERSION/` -> `AUTHOR-MOD-VERSION`
+pub fn mod_string_from_download_url(url: &str) -> Option<String> {
+    let parts: Vec<&str> = url.trim_end_matches('/').rsplit('/').take(3).collect();
+    let [version, mod_name, author_name] = parts[..] else {
+        return None;
+    };
+
+    let mod_string = format!("{author_name}-{mod_name}-{version}");
+    mod_string
+        .parse::<ParsedThunderstoreModString>()
+        .ok()
+        .map(|_| mod_string)
+}
+
+/// Downloads and extracts the given package into `target` and returns the package folder
+fn download_and_extract_package(
+    thunderstore_mod_string: &str,
+    download_url: &str,
+    target: &Path,
+) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(target).map_err(|err| err.to_string())?;
+
+    let path = target.join(format!("{thunderstore_mod_string}.zip"));
+    let temp_file = TempFile::new(
+        std::fs::File::options()
+            .read(true)
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(&path)
+            .map_err(|e| e.to_string())?,
+        path,
+    );
+    thermite::core::manage::download(temp_file.file(), download_url)
+        .map_err(|err| err.to_string())?;
+
+    thermite::core::manage::install_with_sanity(
+        thunderstore_mod_string,
+        temp_file.file(),
+        target,
+        fc_sanity_check,
+    )
+    .map_err(|err| err.to_string())?;
+
+    Ok(target.join(thunderstore_mod_string))
+}
+
+/// Checks whether the package names the local mod, either by package name alone or together with
+/// its author, e.g. the mod `Author.Some_Mod` matches the package `Author-Some_Mod`
+fn package_matches_mod_name(package: &ParsedThunderstoreModString, local_names: &[String]) -> bool {
+    let package_name = normalize_name(&package.mod_name);
+    let full_name = normalize_name(&format!("{}{}", package.author_name, package.mod_name));
+    !package_name.is_empty()
+        && local_names
+            .iter()
+            .any(|local_name| *local_name == package_name || *local_name == full_name)
+}
+
+/// Checks whether a mod in the remote package has the same file names and sizes as the local
+/// mod folder, so that only likely matches have to be downloaded
+fn remote_package_may_contain_mod(
+    entries: &[ZipEntryInfo],
+    local_files: &BTreeSet<(String, u64)>,
+) -> bool {
+    let mut package_mods: BTreeMap<&str, BTreeSet<(String, u64)>> = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| !entry.name.ends_with('/')) {
+        let Some((mod_folder, path)) = entry
+            .name
+            .strip_prefix("mods/")
+            .and_then(|path| path.split_once('/'))
+        else {
+            continue;
+        };
+        package_mods
+            .entry(mod_folder)
+            .or_default()
+            .insert((path.to_string(), entry.uncompressed_size));
+    }
+    package_mods.values().any(|files| files == local_files)
+}
+
+/// Checks whether any mod inside the extracted package has the exact same files
+/// as the local mod folder
+fn package_contains_mod(package_directory: &Path, local_hashes: &BTreeMap<String, String>) -> bool {
+    let Ok(entries) = std::fs::read_dir(package_directory.join("mods")) else {
+        return false;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .any(|entry| hash_package_files(&entry.path()).is_ok_and(|hashes| hashes == *local_hashes))
+}
+
+/// Matches a mod from the `mods` folder without Thunderstore information against the package
+/// index by name, version and file hashes. If a matching package is found, the mod folder is
+/// replaced by the package so that it can be updated like any other package.
+/// Returns the Thunderstore mod string of the matched package
+#[tauri::command]
+pub async fn import_manual_mod(
+    game_install: GameInstall,
+    mod_name: String,
+) -> Result<String, String> {
+    let installed_mods =
+        legacy::parse_installed_mods(&game_install).map_err(|err| err.to_string())?;
+    let local_mod = installed_mods
+        .iter()
+        .find(|ns_mod| ns_mod.name == mod_name)
+        .cloned()
+        .ok_or_else(|| format!("Mod {mod_name} not found in mods folder"))?;
+
+    if let Some(thunderstore_mod_string) = local_mod.thunderstore_mod_string {
+        return Err(format!(
+            "{mod_name} is already tracked as {thunderstore_mod_string}"
+        ));
+    }
+    let version = local_mod
+        .version
+        .ok_or_else(|| format!("{mod_name} does not specify a version in its mod.json"))?;
+
+    let local_directory = PathBuf::from(&local_mod.directory);
+    let folder_name = local_directory
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let local_hashes = hash_package_files(&local_directory).map_err(|err| err.to_string())?;
+    let local_files: BTreeSet<(String, u64)> = local_hashes
+        .keys()
+        .filter_map(|path| {
+            let size = std::fs::metadata(local_directory.join(path)).ok()?.len();
+            Some((path.clone(), size))
+        })
+        .collect();
+
+    let index = match thermite::api::get_package_index() {
+        Ok(res) => res.to_vec(),
+        Err(err) => return Err(format!("Failed fetching package index: {err}")),
+    };
+
+    // Packages are usually named like the mod, with or without the author prefix
+    let local_names = [normalize_name(&mod_name), normalize_name(&folder_name)];
+    let candidates: Vec<(String, String)> = index
+        .iter()
+        .filter_map(|package| package.versions.get(&version))
+        .filter_map(|package_version| {
+            let mod_string = mod_string_from_download_url(&package_version.url)?;
+            let parsed_mod_string: ParsedThunderstoreModString = mod_string.parse().ok()?;
+            if package_matches_mod_name(&parsed_mod_string, &local_names) {
+                Some((mod_string, package_version.url.clone()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    log::info!("Import candidates for {mod_name}: {candidates:?}");
+
+    let import_directory = Path::new(&game_install.game_path).join("___flightcore-temp/import");
+    let mut matched_package = None;
+    for (thunderstore_mod_string, download_url) in candidates {
+        // Compare file names and sizes first, only download packages that may match
+        match list_remote_zip_entries(&download_url).await {
+            Ok(entries) if !remote_package_may_contain_mod(&entries, &local_files) => {
+                log::info!("Files of {thunderstore_mod_string} don't match, skipping");
+                continue;
+            }
+            Ok(_) => (),
+            Err(err) => log::warn!("Failed listing files of {thunderstore_mod_string}: {err}"),
+        }
+
+        let package_directory = match download_and_extract_package(
+            &thunderstore_mod_string,
+            &download_url,
+            &import_directory,
+        ) {
+            Ok(package_directory) => package_directory,
+            Err(err) => {
+                log::warn!("Failed checking {thunderstore_mod_string}: {err}");
+                continue;
+            }
+        };
+
+        if package_contains_mod(&package_directory, &local_hashes) {
+            matched_package = Some((thunderstore_mod_string, package_directory));
+            break;
+        }
+    }
+
+    let result = match matched_package {
+        Some((thunderstore_mod_string, package_directory)) => {
+            let target = Path::new(&game_install.game_path)
+                .join(&game_install.profile)
+                .join("packages")
+                .join(&thunderstore_mod_string);
+            // Packages may contain several mods, all of which may have been copied in by hand
+            let untracked_directories: Vec<PathBuf> = installed_mods
+                .iter()
+                .filter(|ns_mod| ns_mod.thunderstore_mod_string.is_none())
+                .map(|ns_mod| PathBuf::from(&ns_mod.directory))
+                .collect();
+            convert_to_package(&untracked_directories, &package_directory, &target)
+                .map(|()| thunderstore_mod_string)
+        }
+        None => Err(format!(
+            "No Thunderstore package matching name, version and files of {mod_name} found"
+        )),
+    };
+
+    if let Err(err) = std::fs::remove_dir_all(&import_directory) {
+        log::warn!("Failed cleaning up import folder: {err}");
+    }
+
+    result
+}
+
+/// Moves the extracted package into the profile and removes the untracked mod folders
+/// that are identical to one of the package's mods
+fn convert_to_package(
+    untracked_directories: &[PathBuf],
+    package_directory: &Path,
+    target: &Path,
+) -> Result<(), String> {
+    if target.exists() {
+        return Err(format!("{} is already installed", target.display()));
+    }
+
+    std::fs::create_dir_all(target.parent().unwrap()).map_err(|err| err.to_string())?;
+    std::fs::rename(package_directory, target)
+        .map_err(|err| format!("Failed moving package into profile: {err}"))?;
+
+    if let Err(err) = write_package_file_manifest(target) {
+        log::warn!("Failed writing file manifest due to: {}", err);
+    }
+
+    let package_mods = std::fs::read_dir(target.join("mods")).map_err(|err| err.to_string())?;
+    let package_mod_hashes: Vec<BTreeMap<String, String>> = package_mods
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| hash_package_files(&entry.path()).ok())
+        .collect();
+
+    for local_directory in untracked_directories {
+        let is_part_of_package = hash_package_files(local_directory)
+            .is_ok_and(|hashes| package_mod_hashes.contains(&hashes));
+        if is_part_of_package {
+            log::info!(
+                "Replacing {} with package {}",
+                local_directory.display(),
+                target.display()
+            );
+            std::fs::remove_dir_all(local_directory).map_err(|err| err.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mod_string_from_download_url() {
+        let url = "https://thunderstore.io/package/download/Author/Some_Mod/1.2.3/";
+        assert_eq!(
+            mod_string_from_download_url(url),
+            Some("Author-Some_Mod-1.2.3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_package_matches_mod_name() {
+        let package: ParsedThunderstoreModString = "Author-Some_Mod-1.0.0".parse().unwrap();
+        assert!(package_matches_mod_name(
+            &package,
+            &[normalize_name("Author.Some_Mod")]
+        ));
+        assert!(package_matches_mod_name(
+            &package,
+            &[normalize_name("Some.Mod")]
+        ));
+        assert!(!package_matches_mod_name(
+            &package,
+            &[normalize_name("Other.Some_Mod")]
+        ));
+
+        let package: ParsedThunderstoreModString = "Author-Client-1.0.0".parse().unwrap();
+        assert!(!package_matches_mod_name(
+            &package,
+            &[normalize_name("Someone.Client")]
+        ));
+    }
+
+    #[test]
+    fn test_remote_package_may_contain_mod() {
+        let entry = |name: &str, uncompressed_size: u64| ZipEntryInfo {
+            name: name.to_string(),
+            uncompressed_size,
+        };
+        let entries = [
+            entry("manifest.json", 100),
+            entry("mods/", 0),
+            entry("mods/Author.Some_Mod/mod.json", 20),
+            entry("mods/Author.Some_Mod/mod/main.nut", 30),
+        ];
+        let local_files: BTreeSet<(String, u64)> = [
+            ("mod.json".to_string(), 20),
+            ("mod/main.nut".to_string(), 30),
+        ]
+        .into_iter()
+        .collect();
+        assert!(remote_package_may_contain_mod(&entries, &local_files));
+
+        let changed_files: BTreeSet<(String, u64)> = [
+            ("mod.json".to_string(), 20),
+            ("mod/main.nut".to_string(), 31),
+        ]
+        .into_iter()
+        .collect();
+        assert!(!remote_package_may_contain_mod(&entries, &changed_files));
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Author.Some_Mod"), "authorsomemod");
+    }
+}
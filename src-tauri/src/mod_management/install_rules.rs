@@ -0,0 +1,254 @@
+//! Per-package install behaviour loaded from a rules file
+//!
+//! The rules bundled with FlightCore are used until an up-to-date copy has been fetched,
+//! allowing rules to change without a new FlightCore release.
+use crate::constants::{APP_USER_AGENT, INSTALL_RULES_URL};
+use crate::GameInstall;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+use std::sync::RwLock;
+use tauri::api::dialog::blocking::MessageDialogBuilder;
+use tauri::api::dialog::{MessageDialogButtons, MessageDialogKind};
+
+/// Rules shipped with this FlightCore release
+const BUNDLED_INSTALL_RULES: &str = include_str!("../../install-rules.json");
+
+/// Currently active rules, `None` until loaded
+static INSTALL_RULES: RwLock<Option<Vec<InstallRule>>> = RwLock::new(None);
+
+/// What to do when a package is installed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum InstallRuleAction {
+    /// Refuse to install the package
+    Block,
+    /// Ask the user for confirmation before installing
+    Warn,
+    /// Install the package into the given folder relative to the game folder instead of the
+    /// profile's `packages/` folder. `{profile}` is replaced with the profile name.
+    InstallTo { path: String },
+    /// Only allow installing into the given profile
+    RequireProfile { profile: String },
+    /// Install the package and its dependencies into the given profile, creating it if needed,
+    /// and pass the extra launch arguments when that profile is launched
+    DedicatedProfile {
+        profile: String,
+        #[serde(default)]
+        launch_args: Vec<String>,
+    },
+}
+
+/// Install behaviour for a single Thunderstore package
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstallRule {
+    /// Package in `AUTHOR-MOD` format
+    pub package: String,
+    #[serde(flatten)]
+    pub action: InstallRuleAction,
+    /// Shown to the user when the rule applies
+    pub message: Option<String>,
+    /// Hide the package from the Thunderstore mod listing
+    #[serde(default)]
+    pub hide_from_listing: bool,
+    /// Silently skip the package when another mod depends on it
+    #[serde(default)]
+    pub skip_as_dependency: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct InstallRules {
+    rules: Vec<InstallRule>,
+}
+
+fn parse_install_rules(data: &str) -> Result<Vec<InstallRule>, serde_json::Error> {
+    Ok(serde_json::from_str::<InstallRules>(data)?.rules)
+}
+
+/// Fetches the latest rules, falling back to the bundled ones if that fails
+pub async fn load_install_rules() {
+    let fetched_rules = async {
+        let client = reqwest::Client::new();
+        let res = client
+            .get(INSTALL_RULES_URL)
+            .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let rules = parse_install_rules(&res)?;
+        Ok::<_, anyhow::Error>(rules)
+    }
+    .await;
+
+    let rules = match fetched_rules {
+        Ok(rules) => rules,
+        Err(err) => {
+            log::warn!("Failed fetching install rules, using bundled ones: {err}");
+            parse_install_rules(BUNDLED_INSTALL_RULES).expect("Bundled install rules are invalid")
+        }
+    };
+
+    log::info!("Loaded {} install rules", rules.len());
+    *INSTALL_RULES.write().unwrap() = Some(rules);
+}
+
+/// Returns the currently active rules
+pub fn get_install_rules() -> Vec<InstallRule> {
+    if let Some(rules) = INSTALL_RULES.read().unwrap().as_ref() {
+        return rules.clone();
+    }
+    parse_install_rules(BUNDLED_INSTALL_RULES).expect("Bundled install rules are invalid")
+}
+
+/// Returns the rule applying to the given package, if any
+/// Accepts both `AUTHOR-MOD` and `AUTHOR-MOD-VERSION`
+pub fn get_install_rule(thunderstore_mod_string: &str) -> Option<InstallRule> {
+    get_install_rules().into_iter().find(|rule| {
+        thunderstore_mod_string == rule.package
+            || thunderstore_mod_string.starts_with(&format!("{}-", rule.package))
+    })
+}
+
+/// Checks whether the rule allows installing into the given game install
+/// Returns an error with the reason if it does not
+pub fn check_install_rule(
+    rule: &InstallRule,
+    thunderstore_mod_string: &str,
+    game_install: &GameInstall,
+) -> Result<(), String> {
+    let message = rule.message.clone().unwrap_or_else(|| {
+        format!("{thunderstore_mod_string} cannot be installed with FlightCore")
+    });
+
+    match &rule.action {
+        InstallRuleAction::Block => Err(message),
+        InstallRuleAction::Warn => {
+            let dialog = MessageDialogBuilder::new(
+                "Mod install warning",
+                format!(
+                    "{message}\n\nPress 'Ok' to continue or 'Cancel' to abort mod installation"
+                ),
+            )
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancel);
+
+            if dialog.show() {
+                Ok(())
+            } else {
+                Err(format!("Install of {thunderstore_mod_string} cancelled"))
+            }
+        }
+        InstallRuleAction::RequireProfile { profile } => {
+            if game_install.profile == *profile {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{thunderstore_mod_string} can only be installed into the profile \"{profile}\""
+                ))
+            }
+        }
+        InstallRuleAction::InstallTo { .. } | InstallRuleAction::DedicatedProfile { .. } => Ok(()),
+    }
+}
+
+/// Checks that the path stays inside the folder it is joined onto
+fn is_contained_relative_path(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// Returns the folder the package should be extracted into
+pub fn get_install_directory(rule: Option<&InstallRule>, game_install: &GameInstall) -> String {
+    if let Some(InstallRuleAction::InstallTo { path }) = rule.map(|rule| &rule.action) {
+        let path = path.replace("{profile}", &game_install.profile);
+        // Rules are downloaded, so never let them point outside the game folder
+        if is_contained_relative_path(&path) {
+            return format!("{}/{}/", game_install.game_path, path);
+        }
+        log::warn!("Ignoring install rule with path \"{path}\" outside the game folder");
+    }
+    format!(
+        "{}/{}/packages/",
+        game_install.game_path, game_install.profile
+    )
+}
+
+/// Returns the extra launch arguments required by packages installed into the given profile
+pub fn get_profile_launch_args(profile: &str) -> Vec<String> {
+    get_install_rules()
+        .into_iter()
+        .find_map(|rule| match rule.action {
+            InstallRuleAction::DedicatedProfile {
+                profile: rule_profile,
+                launch_args,
+            } if rule_profile == profile => Some(launch_args),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_install_rules_are_valid() {
+        let rules = parse_install_rules(BUNDLED_INSTALL_RULES).unwrap();
+        assert!(!rules.is_empty());
+    }
+
+    #[test]
+    fn test_get_install_rule_matches_versioned_mod_string() {
+        let rule = get_install_rule("northstar-Northstar-1.30.0").unwrap();
+        assert_eq!(rule.package, "northstar-Northstar");
+        assert_eq!(rule.action, InstallRuleAction::Block);
+    }
+
+    #[test]
+    fn test_install_to_path_outside_game_folder_is_ignored() {
+        let game_install = GameInstall {
+            game_path: "/games/Titanfall2".to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        let rule_with_path = |path: &str| InstallRule {
+            package: "Author-Mod".to_string(),
+            action: InstallRuleAction::InstallTo {
+                path: path.to_string(),
+            },
+            message: None,
+            hide_from_listing: false,
+            skip_as_dependency: false,
+        };
+
+        assert_eq!(
+            get_install_directory(Some(&rule_with_path("{profile}/plugins")), &game_install),
+            "/games/Titanfall2/R2Northstar/plugins/"
+        );
+        for path in ["../..", "R2Northstar/../../Documents", "/etc", ""] {
+            assert_eq!(
+                get_install_directory(Some(&rule_with_path(path)), &game_install),
+                "/games/Titanfall2/R2Northstar/packages/"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_dedicated_profile_rule() {
+        let rules = parse_install_rules(
+            r#"{"rules": [{"package": "Author-Mod", "action": "dedicated_profile", "profile": "R2Mod", "launch_args": ["-vanilla"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rules[0].action,
+            InstallRuleAction::DedicatedProfile {
+                profile: "R2Mod".to_string(),
+                launch_args: vec!["-vanilla".to_string()],
+            }
+        );
+    }
+}
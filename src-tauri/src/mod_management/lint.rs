@@ -0,0 +1,581 @@
+//! Checking mods for mistakes before they are uploaded
+//!
+//! Validates `mod.json` against the schema Northstar expects and checks that all files it
+//! references actually exist.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::Path;
+use ts_rs::TS;
+
+/// Keys Northstar reads from `mod.json`
+const KNOWN_MOD_JSON_KEYS: [&str; 11] = [
+    "Name",
+    "Description",
+    "Version",
+    "LoadPriority",
+    "RequiredOnClient",
+    "ConVars",
+    "ConCommands",
+    "Scripts",
+    "Localisation",
+    "Dependencies",
+    "PluginDependencies",
+];
+
+/// Flags that can be set on a ConVar by name
+const CONVAR_FLAGS: [&str; 29] = [
+    "FCVAR_NONE",
+    "FCVAR_UNREGISTERED",
+    "FCVAR_DEVELOPMENTONLY",
+    "FCVAR_GAMEDLL",
+    "FCVAR_CLIENTDLL",
+    "FCVAR_HIDDEN",
+    "FCVAR_PROTECTED",
+    "FCVAR_SPONLY",
+    "FCVAR_ARCHIVE",
+    "FCVAR_NOTIFY",
+    "FCVAR_USERINFO",
+    "FCVAR_PRINTABLEONLY",
+    "FCVAR_UNLOGGED",
+    "FCVAR_NEVER_AS_STRING",
+    "FCVAR_REPLICATED",
+    "FCVAR_CHEAT",
+    "FCVAR_SS",
+    "FCVAR_DEMO",
+    "FCVAR_DONTRECORD",
+    "FCVAR_SS_ADDED",
+    "FCVAR_RELEASE",
+    "FCVAR_RELOAD_MATERIALS",
+    "FCVAR_RELOAD_TEXTURES",
+    "FCVAR_NOT_CONNECTED",
+    "FCVAR_MATERIAL_SYSTEM_THREAD",
+    "FCVAR_ARCHIVE_PLAYERPROFILE",
+    "FCVAR_SERVER_CAN_EXECUTE",
+    "FCVAR_SERVER_CANNOT_QUERY",
+    "FCVAR_CLIENTCMD_CAN_EXECUTE",
+];
+
+/// Identifiers allowed in the `RunOn` condition of a script
+const RUN_ON_IDENTIFIERS: [&str; 5] = ["CLIENT", "SERVER", "UI", "MP", "SP"];
+
+/// Script callbacks that can be declared per script
+const SCRIPT_CALLBACKS: [&str; 3] = ["ClientCallback", "ServerCallback", "UICallback"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export)]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+/// A single problem found in a mod
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct LintDiagnostic {
+    /// File the problem was found in
+    pub file: String,
+    /// 1-based line in the file, if known
+    pub line: Option<u32>,
+    pub severity: LintSeverity,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            LintSeverity::Error => "error",
+            LintSeverity::Warning => "warning",
+        };
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}: {}", self.file, line, severity, self.message),
+            None => write!(f, "{}: {}: {}", self.file, severity, self.message),
+        }
+    }
+}
+
+/// Collects diagnostics for a single file
+struct FileLinter<'a> {
+    file: String,
+    contents: &'a str,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl FileLinter<'_> {
+    /// Adds a diagnostic, locating it at the first line containing `needle` if given
+    fn report(&mut self, severity: LintSeverity, needle: Option<&str>, message: String) {
+        let line = needle.and_then(|needle| find_line(self.contents, needle));
+        self.diagnostics.push(LintDiagnostic {
+            file: self.file.clone(),
+            line,
+            severity,
+            message,
+        });
+    }
+
+    fn error(&mut self, needle: Option<&str>, message: String) {
+        self.report(LintSeverity::Error, needle, message);
+    }
+
+    fn warning(&mut self, needle: Option<&str>, message: String) {
+        self.report(LintSeverity::Warning, needle, message);
+    }
+}
+
+/// Returns the 1-based line of the first occurrence of `needle`
+fn find_line(contents: &str, needle: &str) -> Option<u32> {
+    let offset = contents.find(needle)?;
+    Some(contents[..offset].matches('\n').count() as u32 + 1)
+}
+
+/// Checks that a ConVar's `Flags` are either a number or known flag names joined by `|`
+fn check_convar_flags(flags: &Value) -> Result<(), String> {
+    match flags {
+        Value::Number(number) if number.as_u64().is_some() => Ok(()),
+        Value::String(flags) => {
+            for flag in flags.split('|').map(str::trim) {
+                if !CONVAR_FLAGS.contains(&flag) {
+                    return Err(format!("Unknown ConVar flag \"{flag}\""));
+                }
+            }
+            Ok(())
+        }
+        _ => Err("ConVar flags have to be a number or flag names".to_string()),
+    }
+}
+
+/// Checks that a script's `RunOn` only uses known contexts and operators
+fn check_run_on(run_on: &str) -> Result<(), String> {
+    if run_on.trim().is_empty() {
+        return Err("RunOn is empty".to_string());
+    }
+
+    let identifiers = run_on
+        .split(|c: char| "()!&| ".contains(c))
+        .filter(|identifier| !identifier.is_empty());
+    for identifier in identifiers {
+        if !RUN_ON_IDENTIFIERS.contains(&identifier) {
+            return Err(format!("Unknown RunOn context \"{identifier}\""));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `key` is of the expected type if present, returns the value if so
+fn check_type<'a>(
+    linter: &mut FileLinter,
+    object: &'a serde_json::Map<String, Value>,
+    key: &str,
+    expected: &str,
+    matches_type: fn(&Value) -> bool,
+) -> Option<&'a Value> {
+    let value = object.get(key)?;
+    if matches_type(value) {
+        Some(value)
+    } else {
+        linter.error(
+            Some(&format!("\"{key}\"")),
+            format!("\"{key}\" has to be {expected}"),
+        );
+        None
+    }
+}
+
+fn lint_convars(linter: &mut FileLinter, convars: &[Value]) {
+    let mut seen_names = HashSet::new();
+    for convar in convars {
+        let Some(convar) = convar.as_object() else {
+            linter.error(None, "ConVars have to be objects".to_string());
+            continue;
+        };
+
+        let name = match check_type(linter, convar, "Name", "a string", Value::is_string) {
+            Some(name) => name.as_str().unwrap(),
+            None => {
+                if !convar.contains_key("Name") {
+                    linter.error(None, "ConVar is missing \"Name\"".to_string());
+                }
+                continue;
+            }
+        };
+        let needle = format!("\"{name}\"");
+
+        if !seen_names.insert(name) {
+            linter.error(Some(&needle), format!("ConVar {name} is declared twice"));
+        }
+        match convar.get("DefaultValue") {
+            Some(Value::String(_) | Value::Number(_)) => (),
+            Some(_) => linter.error(
+                Some(&needle),
+                format!("DefaultValue of ConVar {name} has to be a string or number"),
+            ),
+            None => linter.error(
+                Some(&needle),
+                format!("ConVar {name} is missing \"DefaultValue\""),
+            ),
+        }
+        check_type(linter, convar, "HelpString", "a string", Value::is_string);
+        if let Some(flags) = convar.get("Flags") {
+            if let Err(err) = check_convar_flags(flags) {
+                linter.error(Some(&needle), format!("{err} on ConVar {name}"));
+            }
+        }
+    }
+}
+
+fn lint_scripts(linter: &mut FileLinter, mod_directory: &Path, scripts: &[Value]) {
+    let mut seen_paths = HashSet::new();
+    let mut seen_callbacks = HashSet::new();
+
+    for script in scripts {
+        let Some(script) = script.as_object() else {
+            linter.error(None, "Scripts have to be objects".to_string());
+            continue;
+        };
+
+        let path = match check_type(linter, script, "Path", "a string", Value::is_string) {
+            Some(path) => path.as_str().unwrap(),
+            None => {
+                if !script.contains_key("Path") {
+                    linter.error(None, "Script is missing \"Path\"".to_string());
+                }
+                continue;
+            }
+        };
+        let needle = format!("\"{path}\"");
+
+        if !seen_paths.insert(path) {
+            linter.warning(Some(&needle), format!("Script {path} is listed twice"));
+        }
+        if !mod_directory
+            .join("mod/scripts/vscripts")
+            .join(path)
+            .is_file()
+        {
+            linter.error(
+                Some(&needle),
+                format!("Script mod/scripts/vscripts/{path} does not exist"),
+            );
+        }
+
+        match script.get("RunOn").and_then(Value::as_str) {
+            Some(run_on) => {
+                if let Err(err) = check_run_on(run_on) {
+                    linter.error(Some(&format!("\"{run_on}\"")), format!("{err} in {path}"));
+                }
+            }
+            None => linter.error(Some(&needle), format!("Script {path} is missing \"RunOn\"")),
+        }
+
+        for callback_type in SCRIPT_CALLBACKS {
+            let Some(callback) = script.get(callback_type) else {
+                continue;
+            };
+            let Some(callback) = callback.as_object() else {
+                linter.error(
+                    Some(&needle),
+                    format!("{callback_type} of {path} has to be an object"),
+                );
+                continue;
+            };
+
+            for (timing, function) in callback {
+                let Some(function) = function.as_str() else {
+                    linter.error(
+                        Some(&needle),
+                        format!("{callback_type}.{timing} of {path} has to be a function name"),
+                    );
+                    continue;
+                };
+                if !seen_callbacks.insert((callback_type, timing.clone(), function.to_string())) {
+                    linter.warning(
+                        Some(&format!("\"{function}\"")),
+                        format!("{callback_type} {function} is registered more than once"),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn lint_localisation(linter: &mut FileLinter, mod_directory: &Path, localisation: &[Value]) {
+    for path in localisation {
+        let Some(path) = path.as_str() else {
+            linter.error(None, "Localisation entries have to be strings".to_string());
+            continue;
+        };
+
+        // Files may exist per language, English is the fallback
+        let resolved_path = path.replace("%language%", "english");
+        if !mod_directory
+            .join("mod/resource")
+            .join(&resolved_path)
+            .is_file()
+        {
+            linter.error(
+                Some(&format!("\"{path}\"")),
+                format!("Localisation file mod/resource/{resolved_path} does not exist"),
+            );
+        }
+    }
+}
+
+/// Checks `mod.json` of the mod in the given folder
+fn lint_mod_json(mod_directory: &Path) -> Vec<LintDiagnostic> {
+    let mod_json_path = mod_directory.join("mod.json");
+    let contents = match std::fs::read_to_string(&mod_json_path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            return vec![LintDiagnostic {
+                file: mod_json_path.display().to_string(),
+                line: None,
+                severity: LintSeverity::Error,
+                message: format!("Failed reading mod.json: {err}"),
+            }]
+        }
+    };
+    let mut linter = FileLinter {
+        file: mod_json_path.display().to_string(),
+        contents: &contents,
+        diagnostics: Vec::new(),
+    };
+
+    let mod_json: Value = match json5::from_str(&contents) {
+        Ok(mod_json) => mod_json,
+        Err(json5::Error::Message { msg, location }) => {
+            linter.diagnostics.push(LintDiagnostic {
+                file: linter.file.clone(),
+                line: location.map(|location| location.line as u32),
+                severity: LintSeverity::Error,
+                message: format!("Invalid JSON: {msg}"),
+            });
+            return linter.diagnostics;
+        }
+    };
+    let Some(mod_json) = mod_json.as_object() else {
+        linter.error(None, "mod.json has to contain an object".to_string());
+        return linter.diagnostics;
+    };
+
+    for key in mod_json.keys() {
+        if !KNOWN_MOD_JSON_KEYS.contains(&key.as_str()) {
+            linter.warning(
+                Some(&format!("\"{key}\"")),
+                format!("Unknown key \"{key}\""),
+            );
+        }
+    }
+
+    if !mod_json.contains_key("Name") {
+        linter.error(None, "\"Name\" is missing".to_string());
+    }
+    check_type(&mut linter, mod_json, "Name", "a string", Value::is_string);
+    check_type(
+        &mut linter,
+        mod_json,
+        "Description",
+        "a string",
+        Value::is_string,
+    );
+    check_type(
+        &mut linter,
+        mod_json,
+        "Version",
+        "a string",
+        Value::is_string,
+    );
+    check_type(
+        &mut linter,
+        mod_json,
+        "LoadPriority",
+        "a number",
+        Value::is_i64,
+    );
+    check_type(
+        &mut linter,
+        mod_json,
+        "RequiredOnClient",
+        "a boolean",
+        Value::is_boolean,
+    );
+    check_type(
+        &mut linter,
+        mod_json,
+        "Dependencies",
+        "an object",
+        Value::is_object,
+    );
+
+    if let Some(convars) = check_type(&mut linter, mod_json, "ConVars", "a list", Value::is_array) {
+        lint_convars(&mut linter, convars.as_array().unwrap());
+    }
+    if let Some(scripts) = check_type(&mut linter, mod_json, "Scripts", "a list", Value::is_array) {
+        lint_scripts(&mut linter, mod_directory, scripts.as_array().unwrap());
+    }
+    if let Some(localisation) = check_type(
+        &mut linter,
+        mod_json,
+        "Localisation",
+        "a list",
+        Value::is_array,
+    ) {
+        lint_localisation(&mut linter, mod_directory, localisation.as_array().unwrap());
+    }
+
+    linter.diagnostics
+}
+
+/// Checks that all rpaks declared in `paks/rpak.json` exist
+fn lint_rpaks(mod_directory: &Path) -> Vec<LintDiagnostic> {
+    let rpak_json_path = mod_directory.join("paks/rpak.json");
+    let Ok(contents) = std::fs::read_to_string(&rpak_json_path) else {
+        return vec![];
+    };
+    let mut linter = FileLinter {
+        file: rpak_json_path.display().to_string(),
+        contents: &contents,
+        diagnostics: Vec::new(),
+    };
+
+    let rpak_json: Value = match json5::from_str(&contents) {
+        Ok(rpak_json) => rpak_json,
+        Err(err) => {
+            linter.error(None, format!("Invalid JSON: {err}"));
+            return linter.diagnostics;
+        }
+    };
+
+    // Rpaks are referenced both as keys and as values, e.g. `"Postload": { "a.rpak": "b.rpak" }`
+    let mut referenced_rpaks = Vec::new();
+    for section in ["Preload", "Postload", "Patch"] {
+        let Some(section) = rpak_json.get(section).and_then(Value::as_object) else {
+            continue;
+        };
+        for (key, value) in section {
+            referenced_rpaks.push(key.clone());
+            if let Some(value) = value.as_str() {
+                referenced_rpaks.push(value.to_string());
+            }
+        }
+    }
+
+    for rpak in referenced_rpaks {
+        if rpak.ends_with(".rpak") && !mod_directory.join("paks").join(&rpak).is_file() {
+            linter.error(
+                Some(&format!("\"{rpak}\"")),
+                format!("Rpak paks/{rpak} does not exist"),
+            );
+        }
+    }
+
+    linter.diagnostics
+}
+
+/// Checks that each audio override in `audio/` comes with its folder of sounds
+fn lint_audio(mod_directory: &Path) -> Vec<LintDiagnostic> {
+    let Ok(entries) = std::fs::read_dir(mod_directory.join("audio")) else {
+        return vec![];
+    };
+
+    let mut diagnostics = Vec::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            continue;
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut linter = FileLinter {
+            file: path.display().to_string(),
+            contents: &contents,
+            diagnostics: Vec::new(),
+        };
+
+        match json5::from_str::<Value>(&contents) {
+            Ok(audio_json) => {
+                if !audio_json.get("EventId").is_some_and(Value::is_array) {
+                    linter.error(None, "\"EventId\" has to be a list".to_string());
+                }
+            }
+            Err(err) => linter.error(None, format!("Invalid JSON: {err}")),
+        }
+
+        if !path.with_extension("").is_dir() {
+            linter.error(
+                None,
+                format!(
+                    "Audio folder {} does not exist",
+                    path.with_extension("").display()
+                ),
+            );
+        }
+
+        diagnostics.extend(linter.diagnostics);
+    }
+
+    diagnostics
+}
+
+/// Lints a single mod folder, i.e. one containing `mod.json`
+fn lint_mod_folder(mod_directory: &Path) -> Vec<LintDiagnostic> {
+    let mut diagnostics = lint_mod_json(mod_directory);
+    diagnostics.extend(lint_rpaks(mod_directory));
+    diagnostics.extend(lint_audio(mod_directory));
+    diagnostics
+}
+
+/// Lints the mod in the given folder, or all mods under `mods/` if it is a package folder
+pub fn lint(path: &Path) -> Vec<LintDiagnostic> {
+    if path.join("mod.json").exists() {
+        return lint_mod_folder(path);
+    }
+
+    let mut mod_directories: Vec<_> = match std::fs::read_dir(path.join("mods")) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join("mod.json").exists())
+            .collect(),
+        Err(_) => vec![],
+    };
+    mod_directories.sort();
+
+    if mod_directories.is_empty() {
+        return vec![LintDiagnostic {
+            file: path.display().to_string(),
+            line: None,
+            severity: LintSeverity::Error,
+            message: "No mod.json found".to_string(),
+        }];
+    }
+
+    mod_directories
+        .iter()
+        .flat_map(|mod_directory| lint_mod_folder(mod_directory))
+        .collect()
+}
+
+/// Lints the mod or package in the given folder
+#[tauri::command]
+pub fn lint_mod(path: String) -> Vec<LintDiagnostic> {
+    lint(Path::new(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_convar_flags() {
+        assert!(check_convar_flags(&Value::from(16)).is_ok());
+        assert!(check_convar_flags(&Value::from("FCVAR_ARCHIVE | FCVAR_CHEAT")).is_ok());
+        assert!(check_convar_flags(&Value::from("FCVAR_ARCHIVE | ARCHIVE")).is_err());
+    }
+
+    #[test]
+    fn test_check_run_on() {
+        assert!(check_run_on("( CLIENT || SERVER ) && MP").is_ok());
+        assert!(check_run_on("CLIENT && !LOBBY").is_err());
+        assert!(check_run_on("").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 // This file contains various mod management functions
 
-use crate::constants::{BLACKLISTED_MODS, CORE_MODS, MODS_WITH_SPECIAL_REQUIREMENTS};
+use crate::constants::{CORE_MODS, DOWNLOAD_ATTEMPTS};
+use crate::util::DownloadVerificationError;
 use async_recursion::async_recursion;
 use thermite::prelude::ThermiteError;
 
@@ -12,8 +13,20 @@ use std::str::FromStr;
 use std::string::ToString;
 use std::{fs, path::PathBuf};
 
+pub mod conflicts;
+pub mod convars;
+mod dedicated_profile;
+pub mod dependencies;
+pub mod import;
+pub mod install_rules;
 mod legacy;
+pub mod lint;
+pub mod packaging;
+pub mod plan;
 mod plugins;
+pub mod presets;
+pub mod vanilla_mode;
+pub mod verify;
 use crate::GameInstall;
 
 #[derive(Debug, Clone)]
@@ -57,6 +70,8 @@ impl std::fmt::Display for ParsedThunderstoreModString {
 pub struct ThunderstoreManifest {
     name: String,
     version_number: String,
+    #[serde(default)]
+    dependencies: Vec<String>,
 }
 
 /// A wrapper around a temporary file handle and its path.
@@ -90,6 +105,35 @@ impl std::ops::Deref for TempFile {
     }
 }
 
+/// Why installing a mod failed
+#[derive(Debug)]
+pub enum ModInstallError {
+    /// Every download attempt failed verification, holds the error of the last one
+    DownloadVerification(DownloadVerificationError),
+    /// Any other failure, described by its message
+    Other(String),
+}
+
+impl std::fmt::Display for ModInstallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModInstallError::DownloadVerification(err) => write!(
+                f,
+                "Download failed verification after {DOWNLOAD_ATTEMPTS} attempts: {err}"
+            ),
+            ModInstallError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ModInstallError {}
+
+impl From<String> for ModInstallError {
+    fn from(message: String) -> Self {
+        ModInstallError::Other(message)
+    }
+}
+
 /// Installs the specified mod
 #[tauri::command]
 pub async fn install_mod_wrapper(
@@ -100,7 +144,7 @@ pub async fn install_mod_wrapper(
         Ok(()) => (),
         Err(err) => {
             log::warn!("{err}");
-            return Err(err);
+            return Err(err.to_string());
         }
     };
     match crate::repair_and_verify::clean_up_download_folder(&game_install, false) {
@@ -144,10 +188,6 @@ pub fn get_enabled_mods(game_install: &GameInstall) -> Result<serde_json::value:
 
 /// Gets all currently installed and enabled/disabled mods to rebuild `enabledmods.json`
 pub fn rebuild_enabled_mods_json(game_install: &GameInstall) -> Result<(), String> {
-    let enabledmods_json_path = format!(
-        "{}/{}/enabledmods.json",
-        game_install.game_path, game_install.profile
-    );
     let mods_and_properties = get_installed_mods_and_properties(game_install.clone())?;
 
     // Create new mapping
@@ -162,13 +202,26 @@ pub fn rebuild_enabled_mods_json(game_install: &GameInstall) -> Result<(), Strin
     let obj = serde_json::Value::Object(my_map);
 
     // Write to file
-    std::fs::write(
-        enabledmods_json_path,
-        serde_json::to_string_pretty(&obj).unwrap(),
-    )
-    .unwrap();
+    write_enabled_mods_json(game_install, &obj)
+}
 
-    Ok(())
+/// Overwrites `enabledmods.json` with the given mapping of mod names to enabled status
+pub fn write_enabled_mods_json(
+    game_install: &GameInstall,
+    mapping: &serde_json::Value,
+) -> Result<(), String> {
+    let enabledmods_json_path = format!(
+        "{}/{}/enabledmods.json",
+        game_install.game_path, game_install.profile
+    );
+
+    match std::fs::write(
+        enabledmods_json_path,
+        serde_json::to_string_pretty(mapping).unwrap(),
+    ) {
+        Ok(()) => Ok(()),
+        Err(err) => Err(format!("Failed writing enabledmods.json: {err}")),
+    }
 }
 
 /// Set the status of a passed mod to enabled/disabled
@@ -393,7 +446,8 @@ pub fn get_installed_mods_and_properties(
     Ok(installed_mods)
 }
 
-async fn get_ns_mod_download_url(thunderstore_mod_string: &str) -> Result<String, String> {
+/// Returns download URL and listed file size of the given mod version
+async fn get_ns_mod_download_url(thunderstore_mod_string: &str) -> Result<(String, u64), String> {
     // TODO: This will crash the thread if not internet connection exist. `match` should be used instead
     let index = thermite::api::get_package_index().unwrap().to_vec();
 
@@ -415,7 +469,7 @@ async fn get_ns_mod_download_url(thunderstore_mod_string: &str) -> Result<String
         // Iterate over all versions of a given mod
         for ns_mod in ns_mod.versions.values() {
             if ns_mod.url.contains(&ts_mod_string_url) {
-                return Ok(ns_mod.url.clone());
+                return Ok((ns_mod.url.clone(), ns_mod.file_size));
             }
         }
     }
@@ -503,9 +557,35 @@ fn delete_older_versions(
     Ok(())
 }
 
-/// Checks whether some mod is correctly formatted
+/// Checks whether the files of a package are laid out correctly
 /// Currently checks whether
 /// - Some `mod.json` exists under `mods/*/mod.json`
+pub fn check_package_layout<P: AsRef<std::path::Path>>(file_paths: &[P]) -> bool {
+    let mut has_mods = false;
+    let mut mod_json_exists = false;
+
+    // Checks for `mods/*/mod.json`
+    for file_path in file_paths {
+        let file_path = file_path.as_ref();
+        if file_path.starts_with("mods/") {
+            has_mods = true;
+            if let Some(name) = file_path.file_name() {
+                if name == "mod.json" {
+                    let parent_path = file_path.parent().unwrap();
+                    if parent_path.parent().unwrap().to_str().unwrap() == "mods" {
+                        mod_json_exists = true;
+                    }
+                }
+            }
+        }
+    }
+
+    has_mods && mod_json_exists
+}
+
+/// Checks whether some mod is correctly formatted
+/// See [`check_package_layout`] for what is checked
+/// Additionally asks the user for confirmation if the mod contains plugins
 fn fc_sanity_check(input: &&fs::File) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
     let mut archive = match zip::read::ZipArchive::new(*input) {
         Ok(archive) => archive,
@@ -516,27 +596,13 @@ fn fc_sanity_check(input: &&fs::File) -> Result<(), Box<dyn Error + Send + Sync
         }
     };
 
-    let mut has_mods = false;
-    let mut mod_json_exists = false;
-
-    // Checks for `mods/*/mod.json`
+    let mut file_paths = Vec::new();
     for i in 0..archive.len() {
         let file = match archive.by_index(i) {
             Ok(file) => file,
             Err(_) => continue,
         };
         let file_path = file.mangled_name();
-        if file_path.starts_with("mods/") {
-            has_mods = true;
-            if let Some(name) = file_path.file_name() {
-                if name == "mod.json" {
-                    let parent_path = file_path.parent().unwrap();
-                    if parent_path.parent().unwrap().to_str().unwrap() == "mods" {
-                        mod_json_exists = true;
-                    }
-                }
-            }
-        }
 
         if file_path.starts_with("plugins/") {
             if let Some(name) = file_path.file_name() {
@@ -550,9 +616,11 @@ fn fc_sanity_check(input: &&fs::File) -> Result<(), Box<dyn Error + Send + Sync
                 }
             }
         }
+
+        file_paths.push(file_path);
     }
 
-    if has_mods && mod_json_exists {
+    if check_package_layout(&file_paths) {
         Ok(())
     } else {
         Err(Box::new(ThermiteError::UnknownError(
@@ -561,6 +629,40 @@ fn fc_sanity_check(input: &&fs::File) -> Result<(), Box<dyn Error + Send + Sync
     }
 }
 
+/// Downloads a mod archive into the file at `path` until it matches what Thunderstore listed
+///
+/// `download` writes the archive into the passed, emptied file. Gives up after
+/// `DOWNLOAD_ATTEMPTS` downloads failed verification.
+fn download_with_retries(
+    path: &str,
+    file_size: u64,
+    mut download: impl FnMut(&fs::File) -> Result<(), String>,
+) -> Result<TempFile, ModInstallError> {
+    let mut attempt = 1;
+    loop {
+        let temp_file = TempFile::new(
+            std::fs::File::options()
+                .read(true)
+                .write(true)
+                .truncate(true)
+                .create(true)
+                .open(path)
+                .map_err(|e| e.to_string())?,
+            path.into(),
+        );
+        download(temp_file.file())?;
+
+        match crate::util::verify_downloaded_archive(temp_file.file(), file_size) {
+            Ok(()) => return Ok(temp_file),
+            Err(err) if attempt < DOWNLOAD_ATTEMPTS => {
+                log::warn!("{err}, retrying download to {path}");
+                attempt += 1;
+            }
+            Err(err) => return Err(ModInstallError::DownloadVerification(err)),
+        }
+    }
+}
+
 // Copied from `libtermite` source code and modified
 // Should be replaced with a library call to libthermite in the future
 /// Download and install mod to the specified target.
@@ -568,7 +670,7 @@ fn fc_sanity_check(input: &&fs::File) -> Result<(), Box<dyn Error + Send + Sync
 pub async fn fc_download_mod_and_install(
     game_install: &GameInstall,
     thunderstore_mod_string: &str,
-) -> Result<(), String> {
+) -> Result<(), ModInstallError> {
     log::info!("Attempting to install \"{thunderstore_mod_string}\" to {game_install:?}");
     // Get mods and download directories
     let download_directory = format!(
@@ -578,54 +680,53 @@ pub async fn fc_download_mod_and_install(
 
     // Early return on empty string
     if thunderstore_mod_string.is_empty() {
-        return Err("Passed empty string".to_string());
+        return Err("Passed empty string".to_string().into());
+    }
+
+    // Check for special install behaviour, e.g. prevent installing Northstar as a mod
+    // While it would fail during install anyway, having explicit error message is nicer
+    let install_rule = install_rules::get_install_rule(thunderstore_mod_string);
+    if let Some(rule) = &install_rule {
+        install_rules::check_install_rule(rule, thunderstore_mod_string, game_install)?;
     }
 
+    // Some packages, together with their dependencies, go into a profile of their own
+    let dedicated_profile = match install_rule.as_ref().map(|rule| &rule.action) {
+        Some(install_rules::InstallRuleAction::DedicatedProfile { profile, .. }) => {
+            dedicated_profile::ensure_dedicated_profile(game_install, profile)?;
+            Some(GameInstall {
+                profile: profile.clone(),
+                ..game_install.clone()
+            })
+        }
+        _ => None,
+    };
+    let game_install = dedicated_profile.as_ref().unwrap_or(game_install);
+
     let deps = match get_mod_dependencies(thunderstore_mod_string).await {
         Ok(deps) => deps,
-        Err(err) => return Err(err.to_string()),
+        Err(err) => return Err(err.to_string().into()),
     };
     log::info!("Mod dependencies: {deps:?}");
 
     // Recursively install dependencies
     for dep in deps {
-        match fc_download_mod_and_install(game_install, &dep).await {
-            Ok(()) => (),
-            Err(err) => {
-                if err == "Cannot install Northstar as a mod!" {
-                    continue; // For Northstar as a dependency, we just skip it
-                } else {
-                    return Err(err);
-                }
-            }
-        };
-    }
-
-    // Prevent installing Northstar as a mod
-    // While it would fail during install anyway, having explicit error message is nicer
-    for blacklisted_mod in BLACKLISTED_MODS {
-        if thunderstore_mod_string.contains(blacklisted_mod) {
-            return Err("Cannot install Northstar as a mod!".to_string());
+        // For Northstar as a dependency, we just skip it
+        if install_rules::get_install_rule(&dep).is_some_and(|rule| rule.skip_as_dependency) {
+            log::info!("Skipping dependency {dep}");
+            continue;
         }
-    }
 
-    // Prevent installing mods that have specific install requirements
-    for special_mod in MODS_WITH_SPECIAL_REQUIREMENTS {
-        if thunderstore_mod_string.contains(special_mod) {
-            return Err(format!(
-                "{} has special install requirements and cannot be installed with FlightCore",
-                thunderstore_mod_string
-            ));
-        }
+        fc_download_mod_and_install(game_install, &dep).await?;
     }
 
     // Get download URL for the specified mod
-    let download_url = get_ns_mod_download_url(thunderstore_mod_string).await?;
+    let (download_url, file_size) = get_ns_mod_download_url(thunderstore_mod_string).await?;
 
     // Create download directory
     match std::fs::create_dir_all(download_directory.clone()) {
         Ok(()) => (),
-        Err(err) => return Err(err.to_string()),
+        Err(err) => return Err(err.to_string().into()),
     };
 
     let path = format!(
@@ -633,27 +734,30 @@ pub async fn fc_download_mod_and_install(
         game_install.game_path
     );
 
-    // Download the mod
-    let temp_file = TempFile::new(
-        std::fs::File::options()
-            .read(true)
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&path)
-            .map_err(|e| e.to_string())?,
-        (&path).into(),
-    );
-    match thermite::core::manage::download(temp_file.file(), download_url) {
-        Ok(_written_bytes) => (),
-        Err(err) => return Err(err.to_string()),
-    };
+    // Download the mod, retrying if the download doesn't match what Thunderstore listed
+    let temp_file = download_with_retries(&path, file_size, |file| {
+        thermite::core::manage::download(file, download_url.clone())
+            .map(|_written_bytes| ())
+            .map_err(|err| err.to_string())
+    })?;
+
+    if dedicated_profile.is_some() {
+        dedicated_profile::install_into_dedicated_profile(
+            thunderstore_mod_string,
+            temp_file.file(),
+            game_install,
+        )?;
+        if let Some(message) = install_rule.and_then(|rule| rule.message) {
+            tauri::api::dialog::blocking::MessageDialogBuilder::new("Mod installed", message)
+                .kind(tauri::api::dialog::MessageDialogKind::Info)
+                .show();
+        }
+        return Ok(());
+    }
 
     // Get directory to install to made up of packages directory and Thunderstore mod string
-    let install_directory = format!(
-        "{}/{}/packages/",
-        game_install.game_path, game_install.profile
-    );
+    let install_directory =
+        install_rules::get_install_directory(install_rule.as_ref(), game_install);
 
     // Extract the mod to the mods directory
     match thermite::core::manage::install_with_sanity(
@@ -667,13 +771,24 @@ pub async fn fc_download_mod_and_install(
             log::warn!("libthermite couldn't install mod {thunderstore_mod_string} due to {err:?}",);
             return match err {
                 ThermiteError::SanityError(e) => Err(
-                    format!("Mod failed sanity check during install. It's probably not correctly formatted. {}", e)
+                    format!("Mod failed sanity check during install. It's probably not correctly formatted. {}", e).into()
                 ),
-                _ => Err(err.to_string()),
+                _ => Err(err.to_string().into()),
             };
         }
     };
 
+    // Record installed files so that they can be verified later on
+    match verify::write_package_file_manifest(
+        &std::path::Path::new(&install_directory).join(thunderstore_mod_string),
+    ) {
+        Ok(()) => (),
+        Err(err) => {
+            // Catch error but ignore
+            log::warn!("Failed writing file manifest due to: {}", err);
+        }
+    };
+
     // Successful package install
     match legacy::delete_legacy_package_install(thunderstore_mod_string, game_install) {
         Ok(()) => (),
@@ -795,3 +910,74 @@ pub fn delete_thunderstore_mod(
     // Try legacy mod installs as fallback
     legacy::delete_thunderstore_mod(game_install, thunderstore_mod_string)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+    use std::io::Write;
+
+    /// Zip archive holding a single uncompressed file, so that its contents are easy to corrupt
+    fn create_archive() -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("mod.json", options).unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_download_with_retries_retries_corrupted_download() {
+        let temp_directory = create_test_directory();
+        let path = temp_directory.path().join("mod.zip");
+        let archive = create_archive();
+        // Same size, but the file inside no longer matches its CRC
+        let mut corrupted = archive.clone();
+        let position = corrupted.windows(5).position(|bytes| bytes == b"hello");
+        corrupted[position.unwrap() + 4] = b'p';
+
+        let mut attempts = 0;
+        let temp_file = download_with_retries(
+            &path.to_string_lossy(),
+            archive.len() as u64,
+            |mut file: &fs::File| {
+                attempts += 1;
+                let data = if attempts == 1 { &corrupted } else { &archive };
+                file.write_all(data).map_err(|err| err.to_string())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(attempts, 2);
+        assert_eq!(std::fs::read(&path).unwrap(), archive);
+        drop(temp_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_download_with_retries_gives_up() {
+        let temp_directory = create_test_directory();
+        let path = temp_directory.path().join("mod.zip");
+        let archive = create_archive();
+
+        let mut attempts = 0;
+        let result = download_with_retries(
+            &path.to_string_lossy(),
+            archive.len() as u64 + 1,
+            |mut file: &fs::File| {
+                attempts += 1;
+                file.write_all(&archive).map_err(|err| err.to_string())
+            },
+        );
+
+        assert_eq!(attempts, DOWNLOAD_ATTEMPTS);
+        assert!(matches!(
+            result,
+            Err(ModInstallError::DownloadVerification(
+                DownloadVerificationError::SizeMismatch { .. }
+            ))
+        ));
+        assert!(!path.exists());
+    }
+}
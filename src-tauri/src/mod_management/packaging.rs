@@ -0,0 +1,236 @@
+//! Building Thunderstore-ready zip files from a mod folder
+//!
+//! The folder is expected to have the same layout as the resulting package, i.e.
+//! `manifest.json`, `icon.png`, `README.md` and the mods under `mods/`.
+use crate::mod_management::{check_package_layout, ParsedThunderstoreModString};
+use crate::util::get_files_recursively;
+use serde::Deserialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Size Thunderstore requires the package icon to have
+const ICON_SIZE: (u32, u32) = (256, 256);
+
+/// Maximum length of the package description accepted by Thunderstore
+const MAX_DESCRIPTION_LENGTH: usize = 250;
+
+/// `manifest.json` of a package as required by Thunderstore
+#[derive(Deserialize, Debug, Clone)]
+struct PackageManifest {
+    name: String,
+    version_number: String,
+    website_url: String,
+    description: String,
+    dependencies: Vec<String>,
+}
+
+/// Reads width and height from the header of a PNG file
+fn read_png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+    // Signature followed by the `IHDR` chunk, which starts with width and height
+    if data.len() < 24 || data[..8] != PNG_SIGNATURE || &data[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(data[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(data[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+/// Returns all files of the folder relative to it, skipping hidden files and folders like `.git`
+/// as well as zip files
+fn get_package_files(folder: &Path) -> Result<Vec<PathBuf>, String> {
+    let files = get_files_recursively(folder).map_err(|err| err.to_string())?;
+    Ok(files
+        .into_iter()
+        .filter_map(|path| path.strip_prefix(folder).ok().map(Path::to_path_buf))
+        .filter(|path| {
+            !path
+                .components()
+                .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+        })
+        // Previously built packages often end up in the mod folder, never package them again
+        .filter(|path| path.extension() != Some(std::ffi::OsStr::new("zip")))
+        .collect())
+}
+
+fn validate_manifest(folder: &Path) -> Result<PackageManifest, Vec<String>> {
+    let data = std::fs::read_to_string(folder.join("manifest.json"))
+        .map_err(|_| vec!["manifest.json is missing".to_string()])?;
+    let manifest: PackageManifest = serde_json::from_str(&data)
+        .map_err(|err| vec![format!("manifest.json is malformed: {err}")])?;
+
+    let mut problems = Vec::new();
+
+    let name_regex = regex::Regex::new(r"^[a-zA-Z0-9_]+$").unwrap();
+    if !name_regex.is_match(&manifest.name) {
+        problems.push(format!(
+            "Name \"{}\" may only contain letters, numbers and underscores",
+            manifest.name
+        ));
+    }
+
+    let version_regex = regex::Regex::new(r"^\d+\.\d+\.\d+$").unwrap();
+    if !version_regex.is_match(&manifest.version_number) {
+        problems.push(format!(
+            "Version \"{}\" is not in the format MAJOR.MINOR.PATCH",
+            manifest.version_number
+        ));
+    }
+
+    if !manifest.website_url.is_empty()
+        && !manifest.website_url.starts_with("https://")
+        && !manifest.website_url.starts_with("http://")
+    {
+        problems.push(format!(
+            "Website URL \"{}\" is not a valid URL",
+            manifest.website_url
+        ));
+    }
+
+    if manifest.description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        problems.push(format!(
+            "Description is longer than {MAX_DESCRIPTION_LENGTH} characters"
+        ));
+    }
+
+    for dependency in &manifest.dependencies {
+        if dependency.parse::<ParsedThunderstoreModString>().is_err() {
+            problems.push(format!(
+                "Dependency \"{dependency}\" is not in the format AUTHOR-MOD-VERSION"
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(manifest)
+    } else {
+        Err(problems)
+    }
+}
+
+/// Checks the mod folder for everything Thunderstore and FlightCore require from a package
+/// Returns a list of problems found, empty if the folder can be packaged
+#[tauri::command]
+pub fn validate_mod_folder_for_packaging(source_path: String) -> Vec<String> {
+    let folder = Path::new(&source_path);
+    let mut problems = Vec::new();
+
+    if let Err(manifest_problems) = validate_manifest(folder) {
+        problems.extend(manifest_problems);
+    }
+
+    match std::fs::read(folder.join("icon.png")) {
+        Ok(data) => match read_png_dimensions(&data) {
+            Some(ICON_SIZE) => (),
+            Some((width, height)) => problems.push(format!(
+                "icon.png is {width}x{height} but has to be {}x{}",
+                ICON_SIZE.0, ICON_SIZE.1
+            )),
+            None => problems.push("icon.png is not a PNG image".to_string()),
+        },
+        Err(_) => problems.push("icon.png is missing".to_string()),
+    }
+
+    if !folder.join("README.md").is_file() {
+        problems.push("README.md is missing".to_string());
+    }
+
+    // Same check as used when installing a package
+    match get_package_files(folder) {
+        Ok(files) => {
+            if !check_package_layout(&files) {
+                problems.push("No mod found under mods/*/mod.json".to_string());
+            }
+        }
+        Err(err) => problems.push(format!("Failed reading folder: {err}")),
+    }
+
+    problems
+}
+
+/// Packages the mod folder into a zip that can be uploaded to Thunderstore
+/// Returns the path of the created zip file
+#[tauri::command]
+pub fn package_mod_folder(source_path: String, output_directory: String) -> Result<String, String> {
+    let problems = validate_mod_folder_for_packaging(source_path.clone());
+    if !problems.is_empty() {
+        return Err(problems.join("\n"));
+    }
+
+    let folder = Path::new(&source_path);
+    let manifest = validate_manifest(folder).map_err(|problems| problems.join("\n"))?;
+
+    // Files are listed before the zip is created as it may be placed inside the mod folder
+    let package_files = get_package_files(folder)?;
+
+    let zip_path = Path::new(&output_directory)
+        .join(format!("{}-{}.zip", manifest.name, manifest.version_number));
+    let zip_file = std::fs::File::create(&zip_path).map_err(|err| err.to_string())?;
+    let mut zip = zip::ZipWriter::new(zip_file);
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for relative_path in package_files {
+        // Zip files always use forward slashes
+        let name = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        zip.start_file(name, options)
+            .map_err(|err| err.to_string())?;
+        let data = std::fs::read(folder.join(&relative_path)).map_err(|err| err.to_string())?;
+        zip.write_all(&data).map_err(|err| err.to_string())?;
+    }
+
+    zip.finish().map_err(|err| err.to_string())?;
+
+    log::info!("Packaged {} into {}", source_path, zip_path.display());
+    Ok(zip_path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    #[test]
+    fn test_read_png_dimensions() {
+        let mut header = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        header.extend([0, 0, 0, 13]);
+        header.extend(b"IHDR");
+        header.extend(256u32.to_be_bytes());
+        header.extend(128u32.to_be_bytes());
+
+        assert_eq!(read_png_dimensions(&header), Some((256, 128)));
+        assert_eq!(read_png_dimensions(b"not a png"), None);
+    }
+
+    #[test]
+    fn test_get_package_files_skips_hidden_files_and_zips() {
+        let temp_directory = create_test_directory();
+        let folder = temp_directory.path();
+        std::fs::create_dir_all(folder.join("mods/Example.Mod")).unwrap();
+        std::fs::create_dir_all(folder.join(".git")).unwrap();
+        for path in [
+            "manifest.json",
+            "mods/Example.Mod/mod.json",
+            ".git/HEAD",
+            "Example_Mod-1.0.0.zip",
+        ] {
+            std::fs::write(folder.join(path), "").unwrap();
+        }
+
+        let mut files = get_package_files(folder).unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("manifest.json"),
+                PathBuf::from("mods/Example.Mod/mod.json")
+            ]
+        );
+    }
+}
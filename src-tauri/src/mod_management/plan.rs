@@ -0,0 +1,417 @@
+//! Dry runs of installs, listing what an install would change without changing anything
+use crate::mod_management::{install_rules, legacy, ParsedThunderstoreModString};
+use crate::util::list_remote_zip_entries;
+use crate::GameInstall;
+use async_recursion::async_recursion;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use ts_rs::TS;
+
+/// Everything an install is about to do
+/// Paths are relative to the game folder
+#[derive(Serialize, Deserialize, Debug, Clone, Default, TS)]
+#[ts(export)]
+pub struct InstallPlan {
+    /// Packages that will be downloaded, in install order
+    pub packages: Vec<String>,
+    /// Packages pulled in as dependencies of the requested package
+    pub dependencies: Vec<String>,
+    /// Files and folders that will be removed
+    pub paths_to_delete: Vec<String>,
+    /// Files and folders that will be written
+    pub paths_to_write: Vec<String>,
+    /// Total size of all downloads in bytes
+    pub download_size: u64,
+    /// Things to point out before confirming, e.g. warnings an actual install would show
+    pub warnings: Vec<String>,
+}
+
+/// Download URL, size and dependencies of a package version
+struct PackageInfo {
+    download_url: String,
+    file_size: u64,
+    dependencies: Vec<String>,
+}
+
+/// Looks up a package version in the Thunderstore package index
+fn find_package(
+    index: &[thermite::model::Mod],
+    thunderstore_mod_string: &str,
+) -> Result<PackageInfo, String> {
+    let parsed_mod_string: ParsedThunderstoreModString = thunderstore_mod_string
+        .parse()
+        .map_err(|_| "Failed to parse mod string".to_string())?;
+    let ts_mod_string_url = format!(
+        "{}/{}/{}",
+        parsed_mod_string.author_name, parsed_mod_string.mod_name, parsed_mod_string.version
+    );
+
+    index
+        .iter()
+        .flat_map(|ns_mod| ns_mod.versions.values())
+        .find(|ns_mod| ns_mod.url.contains(&ts_mod_string_url))
+        .map(|ns_mod| PackageInfo {
+            download_url: ns_mod.url.clone(),
+            file_size: ns_mod.file_size,
+            dependencies: ns_mod.deps.clone(),
+        })
+        .ok_or_else(|| format!("Could not find {thunderstore_mod_string} on Thunderstore"))
+}
+
+/// Turns a path inside the game folder into one relative to it
+pub fn relative_to_game_path(game_install: &GameInstall, path: &Path) -> String {
+    let relative_path = path.strip_prefix(&game_install.game_path).unwrap_or(path);
+    relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Adds the files of the package, as placed into `target_directory`, to the plan
+///
+/// Falls back to only listing `target_directory` if the archive can't be inspected remotely
+async fn add_package_files(
+    plan: &mut InstallPlan,
+    game_install: &GameInstall,
+    download_url: &str,
+    target_directory: &Path,
+    map_entry: impl Fn(&str) -> Option<String>,
+) {
+    match list_remote_zip_entries(download_url).await {
+        Ok(entries) => plan.paths_to_write.extend(
+            entries
+                .iter()
+                .filter(|entry| !entry.name.ends_with('/'))
+                .filter_map(|entry| map_entry(&entry.name))
+                .map(|entry| relative_to_game_path(game_install, &target_directory.join(entry))),
+        ),
+        Err(err) => {
+            log::warn!("Failed listing files of {download_url}: {err}");
+            plan.warnings.push(format!(
+                "Couldn't list the files of {}, showing folders only",
+                relative_to_game_path(game_install, target_directory)
+            ));
+            plan.paths_to_write
+                .push(relative_to_game_path(game_install, target_directory));
+        }
+    }
+}
+
+/// Mirrors what `fc_download_mod_and_install` does for the given package and its dependencies
+/// Packages are looked up with `find_package`, so that the index is only fetched once
+#[async_recursion]
+async fn plan_package(
+    plan: &mut InstallPlan,
+    game_install: &GameInstall,
+    thunderstore_mod_string: &str,
+    is_dependency: bool,
+    find_package: &(dyn Fn(&str) -> Result<PackageInfo, String> + Sync),
+) -> Result<(), String> {
+    // Packages shared by multiple dependencies are only installed once
+    if plan
+        .packages
+        .iter()
+        .any(|package| package == thunderstore_mod_string)
+    {
+        return Ok(());
+    }
+    let parsed_mod_string: ParsedThunderstoreModString = thunderstore_mod_string
+        .parse()
+        .map_err(|_| "Failed to parse mod string".to_string())?;
+
+    // Warnings are collected instead of asking for confirmation like an actual install does
+    let install_rule = install_rules::get_install_rule(thunderstore_mod_string);
+    match &install_rule {
+        Some(rule) if rule.action == install_rules::InstallRuleAction::Warn => plan.warnings.push(
+            rule.message
+                .clone()
+                .unwrap_or_else(|| format!("{thunderstore_mod_string} may not work correctly")),
+        ),
+        Some(rule) => {
+            install_rules::check_install_rule(rule, thunderstore_mod_string, game_install)?
+        }
+        None => (),
+    }
+
+    let dedicated_profile = match install_rule.as_ref().map(|rule| &rule.action) {
+        Some(install_rules::InstallRuleAction::DedicatedProfile { profile, .. }) => {
+            let profile_path = Path::new(&game_install.game_path).join(profile);
+            if !profile_path.is_dir() {
+                plan.paths_to_write
+                    .push(relative_to_game_path(game_install, &profile_path));
+            }
+            Some(GameInstall {
+                profile: profile.clone(),
+                ..game_install.clone()
+            })
+        }
+        _ => None,
+    };
+    let game_install = dedicated_profile.as_ref().unwrap_or(game_install);
+
+    let PackageInfo {
+        download_url,
+        file_size,
+        dependencies,
+    } = find_package(thunderstore_mod_string)?;
+    for dep in dependencies {
+        if install_rules::get_install_rule(&dep).is_some_and(|rule| rule.skip_as_dependency) {
+            continue;
+        }
+        plan_package(plan, game_install, &dep, true, find_package).await?;
+    }
+
+    plan.packages.push(thunderstore_mod_string.to_string());
+    if is_dependency {
+        plan.dependencies.push(thunderstore_mod_string.to_string());
+    }
+    plan.download_size += file_size;
+
+    // Older versions of the package, whether installed as package or legacy mod, get replaced
+    if let Ok(legacy_mods) = legacy::parse_installed_mods(game_install) {
+        for legacy_mod in legacy_mods {
+            let is_same_package = legacy_mod
+                .thunderstore_mod_string
+                .and_then(|mod_string| mod_string.parse::<ParsedThunderstoreModString>().ok())
+                .is_some_and(|mod_string| {
+                    mod_string.author_name == parsed_mod_string.author_name
+                        && mod_string.mod_name == parsed_mod_string.mod_name
+                });
+            if is_same_package {
+                plan.paths_to_delete.push(relative_to_game_path(
+                    game_install,
+                    Path::new(&legacy_mod.directory),
+                ));
+            }
+        }
+    }
+
+    if dedicated_profile.is_some() {
+        // Mods are extracted directly into the `mods` folder of the profile
+        let mods_directory = Path::new(&game_install.game_path)
+            .join(&game_install.profile)
+            .join("mods");
+        add_package_files(
+            plan,
+            game_install,
+            &download_url,
+            &mods_directory,
+            |entry| entry.strip_prefix("mods/").map(str::to_string),
+        )
+        .await;
+        return Ok(());
+    }
+
+    let packages_directory = Path::new(&game_install.game_path)
+        .join(&game_install.profile)
+        .join("packages");
+    if let Ok(entries) = std::fs::read_dir(&packages_directory) {
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            let is_other_version = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.parse::<ParsedThunderstoreModString>().ok())
+                .is_some_and(|mod_string| {
+                    mod_string.author_name == parsed_mod_string.author_name
+                        && mod_string.mod_name == parsed_mod_string.mod_name
+                        && mod_string.version != parsed_mod_string.version
+                });
+            if is_other_version && path.is_dir() {
+                plan.paths_to_delete
+                    .push(relative_to_game_path(game_install, &path));
+            }
+        }
+    }
+
+    let install_directory = Path::new(&install_rules::get_install_directory(
+        install_rule.as_ref(),
+        game_install,
+    ))
+    .join(thunderstore_mod_string);
+    add_package_files(
+        plan,
+        game_install,
+        &download_url,
+        &install_directory,
+        |entry| Some(entry.to_string()),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Lists what installing the given package, including its dependencies, would change
+#[tauri::command]
+pub async fn plan_mod_install(
+    game_install: GameInstall,
+    thunderstore_mod_string: String,
+) -> Result<InstallPlan, String> {
+    let index = match thermite::api::get_package_index() {
+        Ok(res) => res.to_vec(),
+        Err(err) => {
+            log::warn!("Failed fetching package index due to: {err}");
+            return Err("Failed to connect to Thunderstore.".to_string());
+        }
+    };
+
+    let mut plan = InstallPlan::default();
+    plan_package(
+        &mut plan,
+        &game_install,
+        &thunderstore_mod_string,
+        false,
+        &|mod_string| find_package(&index, mod_string),
+    )
+    .await?;
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{create_test_directory, serve_test_responses, test_http_response};
+    use std::collections::HashMap;
+
+    /// Plans installing `thunderstore_mod_string` with packages looked up in `packages`
+    /// Package files can't be listed, as the stand-in server serving them has none
+    async fn plan_with_packages(
+        game_install: &GameInstall,
+        thunderstore_mod_string: &str,
+        packages: &[(&str, u64, &[&str])],
+        listed_packages: usize,
+    ) -> InstallPlan {
+        let (url, server) = serve_test_responses(vec![
+            test_http_response("404 Not Found", "");
+            listed_packages
+        ]);
+        let packages: HashMap<_, _> = packages
+            .iter()
+            .map(|(name, file_size, dependencies)| (*name, (*file_size, *dependencies)))
+            .collect();
+        let find_package = |mod_string: &str| -> Result<PackageInfo, String> {
+            let (file_size, dependencies) = packages
+                .get(mod_string)
+                .ok_or_else(|| format!("Could not find {mod_string} on Thunderstore"))?;
+            Ok(PackageInfo {
+                download_url: format!("{url}/{mod_string}.zip"),
+                file_size: *file_size,
+                dependencies: dependencies.iter().map(|dep| dep.to_string()).collect(),
+            })
+        };
+
+        let mut plan = InstallPlan::default();
+        plan_package(
+            &mut plan,
+            game_install,
+            thunderstore_mod_string,
+            false,
+            &find_package,
+        )
+        .await
+        .unwrap();
+        server.join().unwrap();
+        plan
+    }
+
+    fn create_game_install(game_path: &Path) -> GameInstall {
+        std::fs::create_dir_all(game_path.join("R2Northstar/mods")).unwrap();
+        GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_mod_install() {
+        let temp_directory = create_test_directory();
+        let game_install = create_game_install(temp_directory.path());
+
+        let plan = plan_with_packages(
+            &game_install,
+            "Tester-Mod-2.0.0",
+            &[
+                (
+                    "Tester-Mod-2.0.0",
+                    100,
+                    &[
+                        "Tester-Library-1.0.0",
+                        "Tester-Other-1.0.0",
+                        "northstar-Northstar-1.0.0",
+                    ],
+                ),
+                ("Tester-Library-1.0.0", 10, &[]),
+                ("Tester-Other-1.0.0", 1, &["Tester-Library-1.0.0"]),
+            ],
+            3,
+        )
+        .await;
+
+        // Dependencies come first and shared ones are only installed once
+        assert_eq!(
+            plan.packages,
+            vec![
+                "Tester-Library-1.0.0",
+                "Tester-Other-1.0.0",
+                "Tester-Mod-2.0.0"
+            ]
+        );
+        assert_eq!(
+            plan.dependencies,
+            vec!["Tester-Library-1.0.0", "Tester-Other-1.0.0"]
+        );
+        assert_eq!(plan.download_size, 111);
+        assert!(plan.paths_to_delete.is_empty());
+        assert_eq!(
+            plan.paths_to_write,
+            vec![
+                "R2Northstar/packages/Tester-Library-1.0.0",
+                "R2Northstar/packages/Tester-Other-1.0.0",
+                "R2Northstar/packages/Tester-Mod-2.0.0"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plan_mod_update() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let game_install = create_game_install(game_path);
+        let packages_directory = game_path.join("R2Northstar/packages");
+        for package in ["Tester-Mod-1.0.0", "Tester-Mod-2.0.0", "Tester-Other-1.0.0"] {
+            std::fs::create_dir_all(packages_directory.join(package)).unwrap();
+        }
+        // Same package installed as legacy mod
+        let legacy_mod = game_path.join("R2Northstar/mods/Tester.Mod");
+        std::fs::create_dir_all(&legacy_mod).unwrap();
+        std::fs::write(legacy_mod.join("mod.json"), r#"{"Name": "Tester.Mod"}"#).unwrap();
+        std::fs::write(
+            legacy_mod.join("manifest.json"),
+            r#"{"name": "Mod", "version_number": "0.9.0"}"#,
+        )
+        .unwrap();
+        std::fs::write(legacy_mod.join("thunderstore_author.txt"), "Tester").unwrap();
+
+        let mut plan = plan_with_packages(
+            &game_install,
+            "Tester-Mod-2.0.0",
+            &[("Tester-Mod-2.0.0", 100, &[])],
+            1,
+        )
+        .await;
+
+        plan.paths_to_delete.sort();
+        assert_eq!(
+            plan.paths_to_delete,
+            vec![
+                "R2Northstar/mods/Tester.Mod",
+                "R2Northstar/packages/Tester-Mod-1.0.0"
+            ]
+        );
+        assert_eq!(plan.download_size, 100);
+    }
+}
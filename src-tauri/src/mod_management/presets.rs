@@ -0,0 +1,218 @@
+//! Named sets of enabled/disabled mods within a profile
+
+use crate::constants::MOD_PRESETS_FILE;
+use crate::mod_management::{get_installed_mods_and_properties, write_enabled_mods_json};
+use crate::GameInstall;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// A named snapshot of which mods are enabled
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct ModPreset {
+    name: String,
+    enabled_mods: BTreeMap<String, bool>,
+}
+
+fn get_presets_path(game_install: &GameInstall) -> String {
+    format!(
+        "{}/{}/{}",
+        game_install.game_path, game_install.profile, MOD_PRESETS_FILE
+    )
+}
+
+fn read_presets(game_install: &GameInstall) -> Result<Vec<ModPreset>, String> {
+    let presets_path = get_presets_path(game_install);
+
+    if !std::path::Path::new(&presets_path).exists() {
+        return Ok(vec![]);
+    }
+
+    let data = std::fs::read_to_string(presets_path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&data).map_err(|err| format!("Failed to read JSON due to: {}", err))
+}
+
+fn write_presets(game_install: &GameInstall, presets: &[ModPreset]) -> Result<(), String> {
+    std::fs::write(
+        get_presets_path(game_install),
+        serde_json::to_string_pretty(presets).unwrap(),
+    )
+    .map_err(|err| err.to_string())
+}
+
+/// Returns all presets of the given profile
+#[tauri::command]
+pub fn list_mod_presets(game_install: GameInstall) -> Result<Vec<ModPreset>, String> {
+    read_presets(&game_install)
+}
+
+/// Stores the current enabled state of all mods as a preset
+/// Overwrites an existing preset of the same name
+#[tauri::command]
+pub fn save_mod_preset(game_install: GameInstall, preset_name: String) -> Result<(), String> {
+    if preset_name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+
+    let enabled_mods = get_installed_mods_and_properties(game_install.clone())?
+        .into_iter()
+        .map(|ns_mod| (ns_mod.name, ns_mod.enabled))
+        .collect();
+
+    let mut presets = read_presets(&game_install)?;
+    match presets.iter_mut().find(|preset| preset.name == preset_name) {
+        Some(preset) => preset.enabled_mods = enabled_mods,
+        None => presets.push(ModPreset {
+            name: preset_name,
+            enabled_mods,
+        }),
+    }
+
+    write_presets(&game_install, &presets)
+}
+
+/// Enables and disables mods according to the given preset
+/// Mods installed after the preset was saved keep their current state
+#[tauri::command]
+pub fn apply_mod_preset(game_install: GameInstall, preset_name: String) -> Result<(), String> {
+    let presets = read_presets(&game_install)?;
+    let preset = presets
+        .iter()
+        .find(|preset| preset.name == preset_name)
+        .ok_or_else(|| format!("Preset {preset_name} not found"))?;
+
+    let mut mapping = serde_json::Map::new();
+    for ns_mod in get_installed_mods_and_properties(game_install.clone())? {
+        let is_enabled = preset
+            .enabled_mods
+            .get(&ns_mod.name)
+            .copied()
+            .unwrap_or(ns_mod.enabled);
+        mapping.insert(ns_mod.name, serde_json::Value::Bool(is_enabled));
+    }
+
+    log::info!("Applying mod preset {preset_name}");
+    write_enabled_mods_json(&game_install, &serde_json::Value::Object(mapping))
+}
+
+/// Renames a preset
+#[tauri::command]
+pub fn rename_mod_preset(
+    game_install: GameInstall,
+    old_name: String,
+    new_name: String,
+) -> Result<(), String> {
+    if new_name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+
+    let mut presets = read_presets(&game_install)?;
+    if presets.iter().any(|preset| preset.name == new_name) {
+        return Err(format!("Preset {new_name} already exists"));
+    }
+
+    match presets.iter_mut().find(|preset| preset.name == old_name) {
+        Some(preset) => preset.name = new_name,
+        None => return Err(format!("Preset {old_name} not found")),
+    }
+
+    write_presets(&game_install, &presets)
+}
+
+/// Deletes a preset
+#[tauri::command]
+pub fn delete_mod_preset(game_install: GameInstall, preset_name: String) -> Result<(), String> {
+    let mut presets = read_presets(&game_install)?;
+
+    let preset_count = presets.len();
+    presets.retain(|preset| preset.name != preset_name);
+    if presets.len() == preset_count {
+        return Err(format!("Preset {preset_name} not found"));
+    }
+
+    write_presets(&game_install, &presets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mod_management::get_enabled_mods;
+    use crate::util::create_test_directory;
+    use std::path::Path;
+
+    /// Creates a profile with the given legacy mods installed
+    fn create_game_install(game_path: &Path, mod_names: &[&str]) -> GameInstall {
+        for name in mod_names {
+            let mod_directory = game_path.join("R2Northstar/mods").join(name);
+            std::fs::create_dir_all(&mod_directory).unwrap();
+            std::fs::write(
+                mod_directory.join("mod.json"),
+                format!(r#"{{"Name": "{name}"}}"#),
+            )
+            .unwrap();
+        }
+        GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        }
+    }
+
+    fn set_enabled_mods(game_install: &GameInstall, mapping: serde_json::Value) {
+        write_enabled_mods_json(game_install, &mapping).unwrap();
+    }
+
+    #[test]
+    fn test_save_and_apply_mod_preset() {
+        let temp_directory = create_test_directory();
+        let game_install = create_game_install(temp_directory.path(), &["Mod.A", "Mod.B"]);
+        set_enabled_mods(
+            &game_install,
+            serde_json::json!({"Mod.A": true, "Mod.B": false}),
+        );
+        save_mod_preset(game_install.clone(), "Competitive".to_string()).unwrap();
+
+        let presets = list_mod_presets(game_install.clone()).unwrap();
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].name, "Competitive");
+
+        set_enabled_mods(
+            &game_install,
+            serde_json::json!({"Mod.A": false, "Mod.B": true}),
+        );
+        apply_mod_preset(game_install.clone(), "Competitive".to_string()).unwrap();
+        assert_eq!(
+            get_enabled_mods(&game_install).unwrap(),
+            serde_json::json!({"Mod.A": true, "Mod.B": false})
+        );
+
+        assert!(apply_mod_preset(game_install, "Unknown".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_apply_mod_preset_with_missing_mod() {
+        let temp_directory = create_test_directory();
+        let game_install = create_game_install(temp_directory.path(), &["Mod.A", "Mod.Removed"]);
+        set_enabled_mods(
+            &game_install,
+            serde_json::json!({"Mod.A": false, "Mod.Removed": true}),
+        );
+        save_mod_preset(game_install.clone(), "Casual".to_string()).unwrap();
+
+        // One mod of the preset is uninstalled and another one is installed afterwards
+        std::fs::remove_dir_all(temp_directory.path().join("R2Northstar/mods/Mod.Removed"))
+            .unwrap();
+        create_game_install(temp_directory.path(), &["Mod.New"]);
+        set_enabled_mods(
+            &game_install,
+            serde_json::json!({"Mod.A": true, "Mod.New": false}),
+        );
+
+        apply_mod_preset(game_install.clone(), "Casual".to_string()).unwrap();
+        assert_eq!(
+            get_enabled_mods(&game_install).unwrap(),
+            serde_json::json!({"Mod.A": false, "Mod.New": false})
+        );
+    }
+}
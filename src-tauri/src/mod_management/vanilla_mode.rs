@@ -0,0 +1,182 @@
+//! Temporarily disabling all client-required mods to join vanilla-compatible servers
+
+use crate::constants::{CORE_MODS, VANILLA_MODE_SNAPSHOT_FILE};
+use crate::mod_management::{get_installed_mods_and_properties, write_enabled_mods_json};
+use crate::GameInstall;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// The parts of `mod.json` relevant for vanilla compatibility
+#[derive(Deserialize, Debug, Clone)]
+struct ModJsonRequiredOnClient {
+    #[serde(rename = "RequiredOnClient", default)]
+    required_on_client: bool,
+}
+
+fn get_snapshot_path(game_install: &GameInstall) -> String {
+    format!(
+        "{}/{}/{}",
+        game_install.game_path, game_install.profile, VANILLA_MODE_SNAPSHOT_FILE
+    )
+}
+
+/// Checks whether the mod in the given folder sets `RequiredOnClient`
+fn is_required_on_client(mod_directory: &str) -> bool {
+    let mod_json_path = format!("{}/mod.json", mod_directory);
+    let data = match std::fs::read_to_string(&mod_json_path) {
+        Ok(data) => data,
+        Err(err) => {
+            log::warn!("Failed reading {}: {}", mod_json_path, err);
+            return false;
+        }
+    };
+
+    match json5::from_str::<ModJsonRequiredOnClient>(&data) {
+        Ok(parsed_json) => parsed_json.required_on_client,
+        Err(err) => {
+            log::warn!("Failed parsing {} with {}", mod_json_path, err);
+            false
+        }
+    }
+}
+
+/// Returns whether vanilla-compatible mode is currently active for the given profile
+#[tauri::command]
+pub fn is_vanilla_compatible_mode_active(game_install: GameInstall) -> bool {
+    std::path::Path::new(&get_snapshot_path(&game_install)).exists()
+}
+
+/// Disables all non-core mods that are required on client and remembers the previous state
+/// Returns the names of the mods that were disabled
+#[tauri::command]
+pub fn enable_vanilla_compatible_mode(game_install: GameInstall) -> Result<Vec<String>, String> {
+    let installed_mods = get_installed_mods_and_properties(game_install.clone())?;
+
+    // Keep the original snapshot if mode is already active
+    let snapshot_path = get_snapshot_path(&game_install);
+    if !std::path::Path::new(&snapshot_path).exists() {
+        let snapshot: BTreeMap<&str, bool> = installed_mods
+            .iter()
+            .map(|ns_mod| (ns_mod.name.as_str(), ns_mod.enabled))
+            .collect();
+        std::fs::write(
+            &snapshot_path,
+            serde_json::to_string_pretty(&snapshot).unwrap(),
+        )
+        .map_err(|err| err.to_string())?;
+    }
+
+    let mut disabled_mods = Vec::new();
+    let mut mapping = serde_json::Map::new();
+    for ns_mod in installed_mods {
+        let mut is_enabled = ns_mod.enabled;
+        if is_enabled
+            && !CORE_MODS.contains(&ns_mod.name.as_str())
+            && is_required_on_client(&ns_mod.directory)
+        {
+            is_enabled = false;
+            disabled_mods.push(ns_mod.name.clone());
+        }
+        mapping.insert(ns_mod.name, serde_json::Value::Bool(is_enabled));
+    }
+
+    log::info!("Disabled client-required mods: {disabled_mods:?}");
+    write_enabled_mods_json(&game_install, &serde_json::Value::Object(mapping))?;
+
+    Ok(disabled_mods)
+}
+
+/// Restores the enabled state of mods from before vanilla-compatible mode was enabled
+/// Mods installed in the meantime keep their current state
+#[tauri::command]
+pub fn disable_vanilla_compatible_mode(game_install: GameInstall) -> Result<(), String> {
+    let snapshot_path = get_snapshot_path(&game_install);
+    let data = match std::fs::read_to_string(&snapshot_path) {
+        Ok(data) => data,
+        Err(_) => return Err("Vanilla-compatible mode is not active".to_string()),
+    };
+    let snapshot: BTreeMap<String, bool> = match serde_json::from_str(&data) {
+        Ok(res) => res,
+        Err(err) => return Err(format!("Failed to read JSON due to: {}", err)),
+    };
+
+    let mut mapping = serde_json::Map::new();
+    for ns_mod in get_installed_mods_and_properties(game_install.clone())? {
+        let is_enabled = snapshot
+            .get(&ns_mod.name)
+            .copied()
+            .unwrap_or(ns_mod.enabled);
+        mapping.insert(ns_mod.name, serde_json::Value::Bool(is_enabled));
+    }
+
+    write_enabled_mods_json(&game_install, &serde_json::Value::Object(mapping))?;
+
+    std::fs::remove_file(snapshot_path).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mod_management::get_enabled_mods;
+    use crate::util::create_test_directory;
+    use std::path::Path;
+
+    fn create_mod(game_path: &Path, name: &str, required_on_client: bool) {
+        let mod_directory = game_path.join("R2Northstar/mods").join(name);
+        std::fs::create_dir_all(&mod_directory).unwrap();
+        std::fs::write(
+            mod_directory.join("mod.json"),
+            format!(r#"{{"Name": "{name}", "RequiredOnClient": {required_on_client}}}"#),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_vanilla_compatible_mode() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        create_mod(game_path, "Northstar.Client", true);
+        create_mod(game_path, "Client.Mod", true);
+        create_mod(game_path, "Disabled.Client.Mod", true);
+        create_mod(game_path, "Server.Mod", false);
+        let game_install = GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        let enabled_mods = serde_json::json!({
+            "Northstar.Client": true,
+            "Client.Mod": true,
+            "Disabled.Client.Mod": false,
+            "Server.Mod": true,
+        });
+        write_enabled_mods_json(&game_install, &enabled_mods).unwrap();
+        assert!(!is_vanilla_compatible_mode_active(game_install.clone()));
+
+        assert_eq!(
+            enable_vanilla_compatible_mode(game_install.clone()).unwrap(),
+            vec!["Client.Mod"]
+        );
+        assert!(is_vanilla_compatible_mode_active(game_install.clone()));
+        assert_eq!(
+            get_enabled_mods(&game_install).unwrap(),
+            serde_json::json!({
+                "Northstar.Client": true,
+                "Client.Mod": false,
+                "Disabled.Client.Mod": false,
+                "Server.Mod": true,
+            })
+        );
+
+        // Enabling again keeps the state from before the first toggle
+        assert!(enable_vanilla_compatible_mode(game_install.clone())
+            .unwrap()
+            .is_empty());
+
+        disable_vanilla_compatible_mode(game_install.clone()).unwrap();
+        assert!(!is_vanilla_compatible_mode_active(game_install.clone()));
+        assert_eq!(get_enabled_mods(&game_install).unwrap(), enabled_mods);
+
+        assert!(disable_vanilla_compatible_mode(game_install).is_err());
+    }
+}
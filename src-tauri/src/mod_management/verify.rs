@@ -0,0 +1,231 @@
+//! Recording and verifying the files of installed Thunderstore packages
+
+use crate::constants::PACKAGE_FILE_MANIFEST;
+use crate::mod_management::ParsedThunderstoreModString;
+use crate::util::{get_files_recursively, sha256_file};
+use crate::GameInstall;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use ts_rs::TS;
+
+/// List of files of a package and their hashes as recorded at install time
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PackageFileManifest {
+    /// Maps path relative to the package folder to SHA-256 hash
+    files: BTreeMap<String, String>,
+}
+
+/// Result of comparing an installed package against its file manifest
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct PackageVerificationResult {
+    pub thunderstore_mod_string: String,
+    pub modified_files: Vec<String>,
+    pub missing_files: Vec<String>,
+    pub extra_files: Vec<String>,
+}
+
+/// Hashes all files in the given package folder
+pub fn hash_package_files(package_directory: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for file_path in get_files_recursively(package_directory)? {
+        let relative_path = file_path.strip_prefix(package_directory)?;
+
+        // Skip the manifest itself
+        if relative_path == Path::new(PACKAGE_FILE_MANIFEST) {
+            continue;
+        }
+
+        // Always use forward slashes so manifests are the same across platforms
+        let relative_path = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        files.insert(relative_path, sha256_file(&file_path)?);
+    }
+    Ok(files)
+}
+
+/// Records the files of a freshly installed package together with their hashes
+pub fn write_package_file_manifest(package_directory: &Path) -> Result<()> {
+    let manifest = PackageFileManifest {
+        files: hash_package_files(package_directory)?,
+    };
+
+    std::fs::write(
+        package_directory.join(PACKAGE_FILE_MANIFEST),
+        serde_json::to_string_pretty(&manifest)?,
+    )
+    .context("Failed writing package file manifest")?;
+
+    Ok(())
+}
+
+/// Compares the files of an installed package against its file manifest
+fn verify_package(package_directory: &Path) -> Result<PackageVerificationResult> {
+    let data = std::fs::read_to_string(package_directory.join(PACKAGE_FILE_MANIFEST))?;
+    let manifest: PackageFileManifest = serde_json::from_str(&data)?;
+
+    let current_files = hash_package_files(package_directory)?;
+
+    let mut modified_files = Vec::new();
+    let mut missing_files = Vec::new();
+    for (path, expected_hash) in &manifest.files {
+        match current_files.get(path) {
+            Some(hash) if hash != expected_hash => modified_files.push(path.clone()),
+            Some(_) => (),
+            None => missing_files.push(path.clone()),
+        }
+    }
+
+    let extra_files = current_files
+        .keys()
+        .filter(|path| !manifest.files.contains_key(*path))
+        .cloned()
+        .collect();
+
+    Ok(PackageVerificationResult {
+        thunderstore_mod_string: package_directory
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string(),
+        modified_files,
+        missing_files,
+        extra_files,
+    })
+}
+
+/// Verifies all installed packages of a profile against their file manifests
+/// Only returns packages that differ from what was originally installed
+#[tauri::command]
+pub fn verify_installed_packages(
+    game_install: GameInstall,
+) -> Result<Vec<PackageVerificationResult>, String> {
+    let packages_folder = format!(
+        "{}/{}/packages",
+        game_install.game_path, game_install.profile
+    );
+
+    let packages_dir = match std::fs::read_dir(packages_folder) {
+        Ok(res) => res,
+        Err(err) => {
+            // No packages folder means no packages to verify
+            log::warn!("{err}");
+            return Ok(vec![]);
+        }
+    };
+
+    let mut results = Vec::new();
+    for entry in packages_dir {
+        let entry_path = entry.map_err(|err| err.to_string())?.path();
+        if !entry_path.is_dir() {
+            continue;
+        }
+
+        let folder_name = entry_path.file_name().unwrap().to_string_lossy();
+        if ParsedThunderstoreModString::from_str(&folder_name).is_err() {
+            continue;
+        }
+
+        // Packages installed before manifests were recorded cannot be verified
+        if !entry_path.join(PACKAGE_FILE_MANIFEST).exists() {
+            log::info!("No file manifest for {folder_name}, skipping");
+            continue;
+        }
+
+        let result = match verify_package(&entry_path) {
+            Ok(result) => result,
+            Err(err) => {
+                log::warn!("Failed verifying {folder_name}: {err}");
+                continue;
+            }
+        };
+
+        if !result.modified_files.is_empty()
+            || !result.missing_files.is_empty()
+            || !result.extra_files.is_empty()
+        {
+            results.push(result);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Freshly installs the given packages, e.g. after verification found them to be modified
+///
+/// The current package folder is kept aside until the reinstall succeeded and put back otherwise.
+#[tauri::command]
+pub async fn reinstall_packages(
+    game_install: GameInstall,
+    thunderstore_mod_strings: Vec<String>,
+) -> Result<(), String> {
+    for thunderstore_mod_string in thunderstore_mod_strings {
+        log::info!("Reinstalling {thunderstore_mod_string}");
+
+        // Ensure we only ever touch folders following the package naming scheme
+        if ParsedThunderstoreModString::from_str(&thunderstore_mod_string).is_err() {
+            return Err(format!(
+                "\"{thunderstore_mod_string}\" is not a valid Thunderstore mod string"
+            ));
+        }
+
+        let package_directory = Path::new(&game_install.game_path)
+            .join(&game_install.profile)
+            .join("packages")
+            .join(&thunderstore_mod_string);
+        let kept_directory = Path::new(&game_install.game_path)
+            .join("___flightcore-temp/reinstall")
+            .join(&thunderstore_mod_string);
+
+        let has_previous_install = package_directory.exists();
+        if has_previous_install {
+            // Same safety check as when deleting, only ever move actual packages
+            if !package_directory.join("manifest.json").exists() {
+                return Err(format!(
+                    "manifest.json does not exist in {}",
+                    package_directory.display()
+                ));
+            }
+            if kept_directory.exists() {
+                std::fs::remove_dir_all(&kept_directory).map_err(|err| err.to_string())?;
+            }
+            std::fs::create_dir_all(kept_directory.parent().unwrap())
+                .map_err(|err| err.to_string())?;
+            std::fs::rename(&package_directory, &kept_directory).map_err(|err| err.to_string())?;
+        }
+
+        let result =
+            super::install_mod_wrapper(game_install.clone(), thunderstore_mod_string.clone()).await;
+        if !has_previous_install {
+            result?;
+            continue;
+        }
+
+        match result {
+            Ok(_) => {
+                if let Err(err) = std::fs::remove_dir_all(&kept_directory) {
+                    log::warn!("Failed removing previous {thunderstore_mod_string}: {err}");
+                }
+            }
+            Err(err) => {
+                log::warn!("Reinstall failed, putting back previous {thunderstore_mod_string}");
+                // Remove whatever the failed install left behind
+                if package_directory.exists() {
+                    std::fs::remove_dir_all(&package_directory).map_err(|err| err.to_string())?;
+                }
+                std::fs::rename(&kept_directory, &package_directory)
+                    .map_err(|err| err.to_string())?;
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(())
+}
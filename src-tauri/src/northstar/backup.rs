@@ -0,0 +1,261 @@
+//! Backing up the installed Northstar before it gets replaced
+//!
+//! Installing swaps the launcher, DLL and core mods file by file. The previous files are
+//! copied aside first so that a failed install can be undone and users can go back to the
+//! previously installed version.
+use crate::constants::{CORE_MODS, NORTHSTAR_BACKUP_DIR};
+use crate::mod_management::plan::relative_to_game_path;
+use crate::northstar::{check_mod_version_number, uninstall};
+use crate::util::{copy_dir_all, get_relative_files_recursively};
+use crate::GameInstall;
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+/// File inside the backup folder listing the files placed by the install that replaced the backup
+const INSTALLED_FILES_LIST: &str = "installed-files.json";
+
+/// Folder storing the backed up files
+/// Files inside it are laid out the same way as in the game folder
+fn get_backup_files_directory(game_install: &GameInstall) -> PathBuf {
+    Path::new(&game_install.game_path)
+        .join(NORTHSTAR_BACKUP_DIR)
+        .join("files")
+}
+
+/// Removes the core mods of the profile
+///
+/// Only removes folders containing a `mod.json` so that a wrong path can never wipe anything else
+pub fn remove_core_mods(game_install: &GameInstall) {
+    for core_mod in CORE_MODS {
+        let path_to_delete = Path::new(&game_install.game_path)
+            .join(&game_install.profile)
+            .join("mods")
+            .join(core_mod);
+        log::info!("Preparing to remove {}", path_to_delete.display());
+
+        // Check if path even exists before we attempt to remove
+        if !path_to_delete.exists() {
+            log::info!("{} does not exist. Skipping", path_to_delete.display());
+            continue;
+        }
+
+        if !path_to_delete.is_dir() {
+            log::error!(
+                "{} exists but is a file? This should never happen",
+                path_to_delete.display()
+            );
+            continue;
+        }
+
+        // Safety check for mod.json
+        // Just so that we won't ever have a https://github.com/ValveSoftware/steam-for-linux/issues/3671 moment
+        if !path_to_delete.join("mod.json").exists() {
+            log::error!(
+                "Missing mod.json for {} this shouldn't happen",
+                path_to_delete.display()
+            );
+            continue;
+        }
+
+        match std::fs::remove_dir_all(&path_to_delete) {
+            Ok(()) => log::info!("Succesfully removed"),
+            Err(err) => log::error!(
+                "Failed removing {} due to {}",
+                path_to_delete.display(),
+                err
+            ),
+        }
+    }
+}
+
+/// Backs up the files of the current Northstar install that installing the files in
+/// `extract_directory` would replace or remove
+///
+/// Replaces any older backup, which may belong to another profile.
+/// Returns `false` if there was no Northstar install to back up.
+pub fn create_backup(game_install: &GameInstall, extract_directory: &Path) -> Result<bool> {
+    let game_path = Path::new(&game_install.game_path);
+    let core_mods_directory = Path::new(&game_install.profile).join("mods");
+
+    // Without core mods there is no Northstar install worth going back to
+    if !game_path
+        .join(&core_mods_directory)
+        .join(CORE_MODS[0])
+        .join("mod.json")
+        .exists()
+    {
+        log::info!("No Northstar install found to back up");
+        return Ok(false);
+    }
+
+    // Core mods are removed entirely, all other files are only overwritten
+    let installed_files = get_relative_files_recursively(extract_directory)?;
+    let mut files_to_backup = installed_files.clone();
+    for core_mod in CORE_MODS {
+        let core_mod_directory = core_mods_directory.join(core_mod);
+        files_to_backup.extend(
+            get_relative_files_recursively(game_path.join(&core_mod_directory))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|path| core_mod_directory.join(path)),
+        );
+    }
+    files_to_backup.sort();
+    files_to_backup.dedup();
+    files_to_backup.retain(|path| game_path.join(path).is_file());
+
+    let backup_directory = Path::new(&game_install.game_path).join(NORTHSTAR_BACKUP_DIR);
+    if backup_directory.exists() {
+        std::fs::remove_dir_all(&backup_directory)?;
+    }
+    let files_directory = get_backup_files_directory(game_install);
+    std::fs::create_dir_all(&files_directory)?;
+
+    // Restoring has to know which files only the new install has in order to remove them
+    let installed_files: Vec<String> = installed_files
+        .iter()
+        .map(|path| relative_to_game_path(game_install, path))
+        .collect();
+    std::fs::write(
+        backup_directory.join(INSTALLED_FILES_LIST),
+        serde_json::to_string_pretty(&installed_files)?,
+    )?;
+
+    log::info!(
+        "Backing up {} files to {}",
+        files_to_backup.len(),
+        files_directory.display()
+    );
+    for path in files_to_backup {
+        let destination = files_directory.join(&path);
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(game_path.join(&path), destination)?;
+    }
+
+    Ok(true)
+}
+
+/// Restores the Northstar install from the backup of the profile
+pub fn restore_backup(game_install: &GameInstall) -> Result<()> {
+    let game_path = Path::new(&game_install.game_path);
+    let files_directory = get_backup_files_directory(game_install);
+    if !files_directory.join(&game_install.profile).is_dir() {
+        return Err(anyhow!("No backup of a previous Northstar install found"));
+    }
+
+    log::info!("Restoring Northstar from {}", files_directory.display());
+
+    // Remove core mods first so that no files of the other version remain
+    remove_core_mods(game_install);
+
+    // Other files only the replacing install added would otherwise be left behind
+    let installed_files: Vec<String> = std::fs::read_to_string(
+        game_path
+            .join(NORTHSTAR_BACKUP_DIR)
+            .join(INSTALLED_FILES_LIST),
+    )
+    .ok()
+    .and_then(|data| serde_json::from_str(&data).ok())
+    .unwrap_or_default();
+    for path in installed_files {
+        if !files_directory.join(&path).exists() && game_path.join(&path).is_file() {
+            log::info!("Removing {path}");
+            std::fs::remove_file(game_path.join(&path))?;
+        }
+    }
+
+    copy_dir_all(&files_directory, game_path)?;
+
+    // Restored files no longer match the hashes recorded by the replaced install
+    let restored_files = get_relative_files_recursively(&files_directory)?;
+    if let Err(err) = uninstall::record_installed_files(game_install, &restored_files) {
+        log::warn!("Failed writing Northstar install manifest due to: {err}");
+    }
+
+    Ok(())
+}
+
+/// Returns the version of the backed up Northstar install of the profile, if there is one
+#[tauri::command]
+pub fn get_northstar_backup_version(game_install: GameInstall) -> Option<String> {
+    let core_mod_directory = get_backup_files_directory(&game_install)
+        .join(&game_install.profile)
+        .join("mods")
+        .join(CORE_MODS[0]);
+    check_mod_version_number(&core_mod_directory.display().to_string()).ok()
+}
+
+/// Rolls back to the previously installed Northstar version
+/// Returns the version that was restored
+#[tauri::command]
+pub fn rollback_northstar(game_install: GameInstall) -> Result<String, String> {
+    let version = match get_northstar_backup_version(game_install.clone()) {
+        Some(version) => version,
+        None => return Err("No previous Northstar install to roll back to".to_string()),
+    };
+
+    match restore_backup(&game_install) {
+        Ok(()) => {
+            log::info!("Rolled back to Northstar {version}");
+            Ok(version)
+        }
+        Err(err) => {
+            let error_message = format!("Failed rolling back Northstar: {err}");
+            log::error!("{error_message}");
+            Err(error_message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    #[test]
+    fn test_restore_backup_removes_added_files() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let extract_directory = game_path.join("___flightcore-temp/extract-dir");
+        let game_install = GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+
+        let write_install = |directory: &Path, version: &str| {
+            for core_mod in CORE_MODS {
+                let mod_directory = directory.join("R2Northstar/mods").join(core_mod);
+                std::fs::create_dir_all(&mod_directory).unwrap();
+                std::fs::write(
+                    mod_directory.join("mod.json"),
+                    format!(r#"{{"Version": "{version}"}}"#),
+                )
+                .unwrap();
+            }
+            std::fs::write(directory.join("NorthstarLauncher.exe"), version).unwrap();
+        };
+        write_install(game_path, "1.0.0");
+        write_install(&extract_directory, "2.0.0");
+        std::fs::write(extract_directory.join("added.dll"), "").unwrap();
+
+        assert!(create_backup(&game_install, &extract_directory).unwrap());
+        remove_core_mods(&game_install);
+        copy_dir_all(&extract_directory, game_path).unwrap();
+
+        assert_eq!(
+            get_northstar_backup_version(game_install.clone()).as_deref(),
+            Some("1.0.0")
+        );
+        restore_backup(&game_install).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(game_path.join("NorthstarLauncher.exe")).unwrap(),
+            "1.0.0"
+        );
+        assert!(!game_path.join("added.dll").exists());
+        assert!(uninstall::get_recorded_files(&game_install)
+            .contains(&"NorthstarLauncher.exe".to_string()));
+    }
+}
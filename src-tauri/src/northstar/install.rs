@@ -1,12 +1,22 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::io::{Seek, SeekFrom, Write};
 use std::time::Duration;
-use std::{cell::RefCell, time::Instant};
+use std::time::Instant;
 use ts_rs::TS;
 
-use crate::constants::{CORE_MODS, NORTHSTAR_DEFAULT_PROFILE, NORTHSTAR_DLL};
+use crate::constants::{
+    APP_USER_AGENT, CORE_MODS, DOWNLOAD_ATTEMPTS, NORTHSTAR_ARCHIVE_CACHE_DIR,
+    NORTHSTAR_DEFAULT_PROFILE, NORTHSTAR_DLL,
+};
+use crate::mod_management::import::mod_string_from_download_url;
+use crate::mod_management::plan::{relative_to_game_path, InstallPlan};
+use crate::northstar::{backup, preflight, uninstall};
 use crate::{
-    util::{extract, move_dir_all},
+    util::{
+        extract, get_relative_files_recursively, list_remote_zip_entries, move_dir_all,
+        verify_downloaded_archive, DownloadVerificationError,
+    },
     GameInstall, InstallType,
 };
 
@@ -29,6 +39,35 @@ struct InstallProgress {
     state: InstallState,
 }
 
+/// Why installing Northstar failed
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub enum NorthstarInstallError {
+    /// Problems found before anything was changed
+    Preflight(Vec<preflight::PreflightError>),
+    Other(String),
+}
+
+impl std::fmt::Display for NorthstarInstallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NorthstarInstallError::Preflight(errors) => {
+                let messages: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+            NorthstarInstallError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for NorthstarInstallError {}
+
+impl From<String> for NorthstarInstallError {
+    fn from(message: String) -> Self {
+        NorthstarInstallError::Other(message)
+    }
+}
+
 /// Installs Northstar to the given path
 #[tauri::command]
 pub async fn install_northstar_wrapper(
@@ -36,7 +75,7 @@ pub async fn install_northstar_wrapper(
     game_install: GameInstall,
     northstar_package_name: Option<String>,
     version_number: Option<String>,
-) -> Result<bool, String> {
+) -> Result<bool, NorthstarInstallError> {
     log::info!("Running Northstar install");
 
     // Get Northstar package name (`Northstar` vs `NorthstarReleaseCandidate`)
@@ -65,65 +104,118 @@ pub async fn update_northstar(
     window: tauri::Window,
     game_install: GameInstall,
     northstar_package_name: Option<String>,
-) -> Result<bool, String> {
+) -> Result<bool, NorthstarInstallError> {
     log::info!("Updating Northstar");
 
     // Simply re-run install with up-to-date version for upate
     install_northstar_wrapper(window, game_install, northstar_package_name, None).await
 }
 
-/// Copied from `papa` source code and modified
-///Install N* from the provided mod
+/// Downloads `url` into the file at `path`
 ///
-///Checks cache, else downloads the latest version
-async fn do_install(
-    window: tauri::Window,
-    nmod: &thermite::model::ModVersion,
-    game_install: GameInstall,
-) -> Result<()> {
-    let filename = format!("northstar-{}.zip", nmod.version);
-    let temp_dir = format!("{}/___flightcore-temp", game_install.game_path);
-    let download_directory = format!("{}/download-dir", temp_dir);
-    let extract_directory = format!("{}/extract-dir", temp_dir);
-
-    log::info!("Attempting to create temporary directory {}", temp_dir);
-    std::fs::create_dir_all(download_directory.clone())?;
-    std::fs::create_dir_all(extract_directory.clone())?;
-
-    let download_path = format!("{}/{}", download_directory, filename);
-    log::info!("Download path: {download_path}");
-
-    let last_emit = RefCell::new(Instant::now()); // Keep track of the last time a signal was emitted
-    let mut nfile = std::fs::File::options()
+/// If a partial download from a previous attempt exists, only the missing part is requested
+/// using a HTTP range request.
+async fn download_resumable(
+    url: &str,
+    path: &std::path::Path,
+    expected_size: u64,
+    mut on_progress: impl FnMut(u64, u64, u64),
+) -> Result<std::fs::File> {
+    let mut file = std::fs::File::options()
         .read(true)
         .write(true)
-        .truncate(true)
         .create(true)
-        .open(download_path)?;
-    thermite::core::manage::download_with_progress(
-        &mut nfile,
-        &nmod.url,
-        |delta, current, total| {
-            if delta != 0 {
-                // Only emit a signal once every 100ms
-                // This way we don't bombard the frontend with events on fast download speeds
-                let time_since_last_emit = Instant::now().duration_since(*last_emit.borrow());
-                if time_since_last_emit >= Duration::from_millis(100) {
-                    window
-                        .emit(
-                            "northstar-install-download-progress",
-                            InstallProgress {
-                                current_downloaded: current,
-                                total_size: total,
-                                state: InstallState::Downloading,
-                            },
-                        )
-                        .unwrap();
-                    *last_emit.borrow_mut() = Instant::now();
-                }
+        .truncate(false)
+        .open(path)?;
+
+    let mut current = file.metadata()?.len();
+    if expected_size != 0 && current > expected_size {
+        log::warn!("Partial download is larger than expected, starting over");
+        file.set_len(0)?;
+        current = 0;
+    }
+
+    if expected_size == 0 || current < expected_size {
+        let client = reqwest::Client::new();
+        let request = |start: u64| {
+            let request = client
+                .get(url)
+                .header(reqwest::header::USER_AGENT, APP_USER_AGENT);
+            if start == 0 {
+                request
+            } else {
+                request.header(reqwest::header::RANGE, format!("bytes={start}-"))
+            }
+        };
+
+        if current != 0 {
+            log::info!("Resuming download at {current} bytes");
+        }
+        let mut response = request(current).send().await?;
+
+        // Server can't or won't continue where we left off, so start over
+        if current != 0 && response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            log::warn!(
+                "Server does not support resuming ({}), starting over",
+                response.status()
+            );
+            file.set_len(0)?;
+            current = 0;
+            if !response.status().is_success() {
+                response = request(0).send().await?;
             }
-        },
-    )?;
+        }
+        let mut response = response.error_for_status()?;
+
+        let total = match expected_size {
+            0 => current + response.content_length().unwrap_or_default(),
+            expected_size => expected_size,
+        };
+
+        file.seek(SeekFrom::Start(current))?;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk)?;
+            current += chunk.len() as u64;
+            on_progress(chunk.len() as u64, current, total);
+        }
+        file.flush()?;
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+/// Replaces the current Northstar install with the files in `extract_directory`
+fn replace_northstar_files(game_install: &GameInstall, extract_directory: &str) -> Result<()> {
+    // Delete previous version here
+    backup::remove_core_mods(game_install);
+
+    for entry in std::fs::read_dir(extract_directory)? {
+        let entry = entry?;
+        let destination = std::path::Path::new(&game_install.game_path).join(entry.file_name());
+
+        log::info!("Installing {}", entry.path().display());
+        if !entry.file_type()?.is_dir() {
+            std::fs::rename(entry.path(), destination)?;
+        } else {
+            move_dir_all(entry.path(), destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Installs Northstar from the given release archive
+///
+/// Extracts it into `temp_dir`, prepares it for the Profile and swaps out the current install
+fn install_from_archive(
+    window: &tauri::Window,
+    archive: std::fs::File,
+    game_install: &GameInstall,
+    temp_dir: &str,
+) -> Result<()> {
+    let extract_directory = format!("{}/extract-dir", temp_dir);
+    std::fs::create_dir_all(&extract_directory)?;
 
     window
         .emit(
@@ -137,7 +229,7 @@ async fn do_install(
         .unwrap();
 
     log::info!("Extracting Northstar...");
-    extract(nfile, std::path::Path::new(&extract_directory))?;
+    extract(archive, std::path::Path::new(&extract_directory))?;
 
     // Prepare Northstar for Installation
     log::info!("Preparing Northstar...");
@@ -147,12 +239,15 @@ async fn do_install(
         // - rename the Profile
 
         // Move DLL into the default R2Northstar Profile
+        // Newer releases already ship it inside the Profile
         let old_dll_path = format!("{}/{}", extract_directory, NORTHSTAR_DLL);
         let new_dll_path = format!(
             "{}/{}/{}",
             extract_directory, NORTHSTAR_DEFAULT_PROFILE, NORTHSTAR_DLL
         );
-        std::fs::rename(old_dll_path, new_dll_path)?;
+        if std::path::Path::new(&old_dll_path).exists() {
+            std::fs::rename(old_dll_path, new_dll_path)?;
+        }
 
         // rename default R2Northstar Profile to the profile we want to use
         let old_profile_path = format!("{}/{}/", extract_directory, NORTHSTAR_DEFAULT_PROFILE);
@@ -162,71 +257,27 @@ async fn do_install(
 
     log::info!("Installing Northstar...");
 
-    // Delete previous version here
-    for core_mod in CORE_MODS {
-        let path_to_delete_string = format!(
-            "{}/{}/mods/{}/",
-            game_install.game_path, game_install.profile, core_mod
-        );
-        log::info!("Preparing to remove {}", path_to_delete_string);
-
-        // Check if folder exists
-        let path_to_delete = std::path::Path::new(&path_to_delete_string);
-
-        // Check if path even exists before we attempt to remove
-        if !path_to_delete.exists() {
-            log::info!("{} does not exist. Skipping", path_to_delete_string);
-            continue;
-        }
-
-        if !path_to_delete.is_dir() {
-            log::error!(
-                "{} exists but is a file? This should never happen",
-                path_to_delete_string
-            );
-            continue;
-        }
-
-        // Safety check for mod.json
-        // Just so that we won't ever have a https://github.com/ValveSoftware/steam-for-linux/issues/3671 moment
-        let mod_json_path = format!("{}/mod.json", path_to_delete_string);
-        let mod_json_path = std::path::Path::new(&mod_json_path);
-
-        if !mod_json_path.exists() {
-            log::error!("Missing mod.json for {path_to_delete_string} this shouldn't happen");
-            continue;
-        }
-
-        // Finally delete file
-        match std::fs::remove_dir_all(path_to_delete) {
-            Ok(()) => {
-                log::info!("Succesfully removed")
+    let installed_files = get_relative_files_recursively(std::path::Path::new(&extract_directory))?;
+    let backup_created =
+        backup::create_backup(game_install, std::path::Path::new(&extract_directory))?;
+    if let Err(err) = replace_northstar_files(game_install, &extract_directory) {
+        if backup_created {
+            log::warn!("Failed installing Northstar, restoring previous install: {err}");
+            if let Err(restore_err) = backup::restore_backup(game_install) {
+                log::error!("Failed restoring previous Northstar install: {restore_err}");
             }
-            Err(err) => {
-                log::error!("Failed removing {} due to {}", path_to_delete_string, err)
-            }
-        };
+        }
+        return Err(err);
     }
 
-    for entry in std::fs::read_dir(extract_directory).unwrap() {
-        let entry = entry.unwrap();
-        let destination = format!(
-            "{}/{}",
-            game_install.game_path,
-            entry.path().file_name().unwrap().to_str().unwrap()
-        );
-
-        log::info!("Installing {}", entry.path().display());
-        if !entry.file_type().unwrap().is_dir() {
-            std::fs::rename(entry.path(), destination)?;
-        } else {
-            move_dir_all(entry.path(), destination)?;
-        }
+    // Record installed files so that Northstar can be uninstalled later on
+    if let Err(err) = uninstall::record_installed_files(game_install, &installed_files) {
+        log::warn!("Failed writing Northstar install manifest due to: {err}");
     }
 
     // Delete old copy
     log::info!("Delete temporary directory");
-    std::fs::remove_dir_all(temp_dir).unwrap();
+    std::fs::remove_dir_all(temp_dir)?;
 
     log::info!("Done installing Northstar!");
     window
@@ -243,17 +294,173 @@ async fn do_install(
     Ok(())
 }
 
+/// Path the release archive of `nmod` is kept at, so it can be reused for repairs
+pub fn get_cached_archive_path(
+    game_install: &GameInstall,
+    nmod: &thermite::model::ModVersion,
+) -> String {
+    // Release candidates share version numbers with releases on Thunderstore
+    let filename = match mod_string_from_download_url(&nmod.url) {
+        Some(mod_string) => format!("{mod_string}.zip"),
+        None => format!("northstar-{}.zip", nmod.version),
+    };
+    format!(
+        "{}/{}/{}",
+        game_install.game_path, NORTHSTAR_ARCHIVE_CACHE_DIR, filename
+    )
+}
+
+/// Removes all cached release archives except the one at `keep_path`
+fn prune_archive_cache(game_install: &GameInstall, keep_path: &str) {
+    let cache_directory = format!("{}/{}", game_install.game_path, NORTHSTAR_ARCHIVE_CACHE_DIR);
+    let Ok(entries) = std::fs::read_dir(cache_directory) else {
+        return;
+    };
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if path != std::path::Path::new(keep_path) {
+            log::info!("Removing cached archive {}", path.display());
+            if let Err(err) = std::fs::remove_file(&path) {
+                log::warn!("Failed removing {}: {err}", path.display());
+            }
+        }
+    }
+}
+
+/// Path a download to `download_path` is kept at until it is complete and verified
+///
+/// Partial downloads live in the temp dir so that an aborted download never ends up in the
+/// game folder. Only verified archives are moved into the archive cache.
+fn get_partial_download_path(download_path: &str) -> std::path::PathBuf {
+    let file_name = std::path::Path::new(download_path)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    std::env::temp_dir().join(format!("flightcore-{file_name}.part"))
+}
+
+/// Moves a file, copying it if it is on a different volume than the destination
+fn move_file(source: &std::path::Path, destination: &str) -> std::io::Result<()> {
+    if std::fs::rename(source, destination).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(source, destination)?;
+    std::fs::remove_file(source)
+}
+
+/// Downloads the release archive of `nmod` to `download_path`, emitting progress to the window
+///
+/// Downloads that don't match what Thunderstore listed are retried.
+pub async fn download_northstar_release(
+    window: &tauri::Window,
+    nmod: &thermite::model::ModVersion,
+    download_path: &str,
+) -> Result<std::fs::File> {
+    log::info!("Download path: {download_path}");
+
+    let mut last_emit = Instant::now(); // Keep track of the last time a signal was emitted
+    let mut on_progress = |delta: u64, current: u64, total: u64| {
+        if delta != 0 {
+            // Only emit a signal once every 100ms
+            // This way we don't bombard the frontend with events on fast download speeds
+            let time_since_last_emit = Instant::now().duration_since(last_emit);
+            if time_since_last_emit >= Duration::from_millis(100) {
+                window
+                    .emit(
+                        "northstar-install-download-progress",
+                        InstallProgress {
+                            current_downloaded: current,
+                            total_size: total,
+                            state: InstallState::Downloading,
+                        },
+                    )
+                    .unwrap();
+                last_emit = Instant::now();
+            }
+        }
+    };
+
+    // Archive may still be cached from an earlier install
+    if let Ok(file) = std::fs::File::open(download_path) {
+        if verify_downloaded_archive(&file, nmod.file_size).is_ok() {
+            log::info!("Using cached archive {download_path}");
+            return Ok(file);
+        }
+    }
+
+    let partial_path = get_partial_download_path(download_path);
+    let mut attempt = 1;
+    loop {
+        let verified_download =
+            download_resumable(&nmod.url, &partial_path, nmod.file_size, &mut on_progress)
+                .await
+                .and_then(|file| {
+                    verify_downloaded_archive(&file, nmod.file_size)?;
+                    Ok(file)
+                });
+
+        match verified_download {
+            Ok(file) => {
+                drop(file);
+                move_file(&partial_path, download_path)?;
+                return Ok(std::fs::File::open(download_path)?);
+            }
+            Err(err) if err.is::<DownloadVerificationError>() => {
+                // Never resume from a broken download
+                std::fs::remove_file(&partial_path)?;
+                if attempt >= DOWNLOAD_ATTEMPTS {
+                    return Err(err.context(format!(
+                        "Download of Northstar {} failed verification after {attempt} attempts",
+                        nmod.version
+                    )));
+                }
+                log::warn!("{err}, retrying download");
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Copied from `papa` source code and modified
+///Install N* from the provided mod
+///
+///Checks cache, else downloads the latest version
+async fn do_install(
+    window: tauri::Window,
+    nmod: &thermite::model::ModVersion,
+    game_install: GameInstall,
+) -> Result<()> {
+    let temp_dir = format!("{}/___flightcore-temp", game_install.game_path);
+    let cache_directory = format!("{}/{}", game_install.game_path, NORTHSTAR_ARCHIVE_CACHE_DIR);
+
+    log::info!("Attempting to create cache directory {}", cache_directory);
+    std::fs::create_dir_all(&cache_directory)?;
+
+    let download_path = get_cached_archive_path(&game_install, nmod);
+    let nfile = download_northstar_release(&window, nmod, &download_path).await?;
+
+    install_from_archive(&window, nfile, &game_install, &temp_dir)?;
+
+    // Only the archive of the current install is needed for repairs
+    prune_archive_cache(&game_install, &download_path);
+
+    Ok(())
+}
+
 pub async fn install_northstar(
     window: tauri::Window,
     game_install: GameInstall,
     northstar_package_name: String,
     version_number: Option<String>,
-) -> Result<String, String> {
+) -> Result<String, NorthstarInstallError> {
     let index = match thermite::api::get_package_index() {
         Ok(res) => res.to_vec(),
         Err(err) => {
             log::warn!("Failed fetching package index due to: {err}");
-            return Err("Failed to connect to Thunderstore.".to_string());
+            return Err("Failed to connect to Thunderstore.".to_string().into());
         }
     };
     let nmod = index
@@ -265,27 +472,201 @@ pub async fn install_northstar(
     // Use passed version or latest if no version was passed
     let version = version_number.as_ref().unwrap_or(&nmod.latest);
 
-    let game_path = game_install.game_path.clone();
-    log::info!("Install path \"{}\"", game_path);
+    log::info!("Install path \"{}\"", game_install.game_path);
+    let nmod_version = nmod.versions.get(version).unwrap();
+
+    // Catch problems like missing permissions before anything is changed
+    let preflight_report = preflight::check_northstar_install(
+        &game_install,
+        &nmod_version.url,
+        nmod_version.file_size,
+    )
+    .await;
+    if !preflight_report.errors.is_empty() {
+        return Err(NorthstarInstallError::Preflight(preflight_report.errors));
+    }
+    if !preflight_report.lock_detection_available {
+        log::info!("Lock detection unavailable, files in use are not checked for");
+    }
 
-    match do_install(window, nmod.versions.get(version).unwrap(), game_install).await {
+    match do_install(window, nmod_version, game_install).await {
         Ok(_) => (),
+        Err(err) => return Err(err.to_string().into()),
+    }
+
+    Ok(nmod.latest.clone())
+}
+
+/// Checks that the entries of a zip file look like a Northstar release
+/// Returns a list of problems found, empty if the layout is valid
+pub fn check_northstar_archive_layout(entry_names: &[String]) -> Vec<String> {
+    // Files outside the `Northstar` folder, like Thunderstore's `manifest.json`, are ignored
+    // when extracting, so only the parts needed to run Northstar are checked
+    let mut problems = Vec::new();
+
+    for core_mod in CORE_MODS {
+        let mod_json = format!("Northstar/{NORTHSTAR_DEFAULT_PROFILE}/mods/{core_mod}/mod.json");
+        if !entry_names.contains(&mod_json) {
+            problems.push(format!("Core mod {core_mod} is missing"));
+        }
+    }
+
+    // Older releases ship the DLL next to the launcher, newer ones inside the Profile
+    let dll_paths = [
+        format!("Northstar/{NORTHSTAR_DLL}"),
+        format!("Northstar/{NORTHSTAR_DEFAULT_PROFILE}/{NORTHSTAR_DLL}"),
+    ];
+    if !dll_paths.iter().any(|path| entry_names.contains(path)) {
+        problems.push(format!("{NORTHSTAR_DLL} is missing"));
+    }
+
+    problems
+}
+
+/// Installs Northstar from a release zip on disk instead of downloading it
+#[tauri::command]
+pub async fn install_northstar_from_zip(
+    window: tauri::Window,
+    game_install: GameInstall,
+    zip_path: String,
+) -> Result<(), String> {
+    log::info!("Installing Northstar from {zip_path}");
+
+    let archive = std::fs::File::open(&zip_path).map_err(|err| err.to_string())?;
+    let entry_names: Vec<String> = match zip::ZipArchive::new(&archive) {
+        Ok(zip) => zip.file_names().map(str::to_string).collect(),
+        Err(err) => return Err(format!("{zip_path} is not a valid zip file: {err}")),
+    };
+
+    let problems = check_northstar_archive_layout(&entry_names);
+    if !problems.is_empty() {
+        return Err(format!(
+            "{zip_path} is not a Northstar release:\n{}",
+            problems.join("\n")
+        ));
+    }
+
+    // Extracting takes a while, so do it off the main thread
+    let temp_dir = format!("{}/___flightcore-temp", game_install.game_path);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        install_from_archive(&window, archive, &game_install, &temp_dir)
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+
+    match result {
+        Ok(()) => Ok(()),
         Err(err) => {
-            if game_path
-                .to_lowercase()
-                .contains(&r"C:\Program Files\".to_lowercase())
-            // default is `C:\Program Files\EA Games\Titanfall2`
-            {
-                return Err(
-                    "Cannot install to default EA App install path, please move Titanfall2 to a different install location.".to_string(),
-                );
-            } else {
-                return Err(err.to_string());
-            }
+            let error_message = format!("Failed installing Northstar from {zip_path}: {err}");
+            log::error!("{error_message}");
+            Err(error_message)
         }
     }
+}
 
-    Ok(nmod.latest.clone())
+/// Maps an entry of a Northstar release zip to where it ends up relative to the game folder
+/// Mirrors what [`install_from_archive`] does when preparing the release for the Profile
+fn map_northstar_archive_entry(entry: &str, profile: &str) -> Option<String> {
+    let path = entry.strip_prefix("Northstar/")?;
+    if profile == NORTHSTAR_DEFAULT_PROFILE {
+        return Some(path.to_string());
+    }
+
+    if path == NORTHSTAR_DLL {
+        return Some(format!("{profile}/{NORTHSTAR_DLL}"));
+    }
+    match path.strip_prefix(&format!("{NORTHSTAR_DEFAULT_PROFILE}/")) {
+        Some(profile_path) => Some(format!("{profile}/{profile_path}")),
+        None => Some(path.to_string()),
+    }
+}
+
+/// Lists what installing the given Northstar version would change
+/// Plans an update to the latest version if no version is passed
+#[tauri::command]
+pub async fn plan_northstar_install(
+    game_install: GameInstall,
+    northstar_package_name: Option<String>,
+    version_number: Option<String>,
+) -> Result<InstallPlan, String> {
+    let northstar_package_name = northstar_package_name
+        .filter(|name| name.len() > 1)
+        .unwrap_or("Northstar".to_string());
+
+    let index = match thermite::api::get_package_index() {
+        Ok(res) => res.to_vec(),
+        Err(err) => {
+            log::warn!("Failed fetching package index due to: {err}");
+            return Err("Failed to connect to Thunderstore.".to_string());
+        }
+    };
+    let nmod = index
+        .iter()
+        .find(|f| f.name.to_lowercase() == northstar_package_name.to_lowercase())
+        .ok_or_else(|| format!("Couldn't find {northstar_package_name} on Thunderstore"))?;
+    let version = version_number.as_ref().unwrap_or(&nmod.latest);
+    let nmod_version = nmod
+        .versions
+        .get(version)
+        .ok_or_else(|| format!("Couldn't find {northstar_package_name} {version}"))?;
+
+    let package = mod_string_from_download_url(&nmod_version.url)
+        .unwrap_or_else(|| format!("{northstar_package_name}-{version}"));
+    Ok(plan_northstar_release(
+        &game_install,
+        package,
+        &nmod_version.url,
+        nmod_version.file_size,
+    )
+    .await)
+}
+
+/// Lists what installing the Northstar release at the given URL would change
+async fn plan_northstar_release(
+    game_install: &GameInstall,
+    package: String,
+    download_url: &str,
+    file_size: u64,
+) -> InstallPlan {
+    let mut plan = InstallPlan {
+        packages: vec![package],
+        download_size: file_size,
+        ..Default::default()
+    };
+
+    // Core mods are replaced entirely
+    for core_mod in CORE_MODS {
+        let core_mod_path = std::path::Path::new(&game_install.game_path)
+            .join(&game_install.profile)
+            .join("mods")
+            .join(core_mod);
+        if core_mod_path.join("mod.json").exists() {
+            plan.paths_to_delete
+                .push(relative_to_game_path(game_install, &core_mod_path));
+        }
+    }
+
+    match list_remote_zip_entries(download_url).await {
+        Ok(entries) => plan.paths_to_write.extend(
+            entries
+                .iter()
+                .filter(|entry| !entry.name.ends_with('/'))
+                .filter_map(|entry| {
+                    map_northstar_archive_entry(&entry.name, &game_install.profile)
+                }),
+        ),
+        Err(err) => {
+            log::warn!("Failed listing files of {download_url}: {err}");
+            plan.warnings
+                .push("Couldn't list the files of the release, showing folders only".to_string());
+            plan.paths_to_write.extend([
+                "NorthstarLauncher.exe".to_string(),
+                game_install.profile.clone(),
+            ]);
+        }
+    }
+
+    plan
 }
 
 /// Attempts to find the game install location
@@ -356,3 +737,218 @@ pub fn find_game_install_location() -> Result<GameInstall, String> {
 
     Err("Could not auto-detect game install location! Please enter it manually.".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{create_test_directory, serve_test_responses, test_http_response};
+
+    /// Downloads from a stand-in server answering with `responses` into a file holding `partial`
+    /// Returns the downloaded file's contents and the requests the server received
+    async fn download_with_responses(
+        partial: Option<&str>,
+        expected_size: u64,
+        responses: Vec<String>,
+    ) -> (String, Vec<String>) {
+        let temp_directory = create_test_directory();
+        let path = temp_directory.path().join("download.zip");
+        if let Some(partial) = partial {
+            std::fs::write(&path, partial).unwrap();
+        }
+
+        let (url, server) = serve_test_responses(responses);
+        download_resumable(&url, &path, expected_size, |_, _, _| ())
+            .await
+            .unwrap();
+        (
+            std::fs::read_to_string(&path).unwrap(),
+            server.join().unwrap(),
+        )
+    }
+
+    fn range_header(request: &str) -> Option<&str> {
+        request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("range").then_some(value.trim())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_download_resumable() {
+        let (contents, requests) =
+            download_with_responses(None, 11, vec![test_http_response("200 OK", "hello world")])
+                .await;
+        assert_eq!(contents, "hello world");
+        assert_eq!(range_header(&requests[0]), None);
+    }
+
+    #[tokio::test]
+    async fn test_download_resumable_resumes() {
+        let (contents, requests) = download_with_responses(
+            Some("hello "),
+            11,
+            vec![test_http_response("206 Partial Content", "world")],
+        )
+        .await;
+        assert_eq!(contents, "hello world");
+        assert_eq!(range_header(&requests[0]), Some("bytes=6-"));
+    }
+
+    #[tokio::test]
+    async fn test_download_resumable_without_range_support() {
+        // Server ignoring the range sends the whole file, which replaces the partial download
+        let (contents, requests) = download_with_responses(
+            Some("hello "),
+            11,
+            vec![test_http_response("200 OK", "hello world")],
+        )
+        .await;
+        assert_eq!(contents, "hello world");
+        assert_eq!(range_header(&requests[0]), Some("bytes=6-"));
+
+        // Server rejecting the range is asked for the whole file again
+        let (contents, requests) = download_with_responses(
+            Some("hello "),
+            11,
+            vec![
+                test_http_response("416 Range Not Satisfiable", ""),
+                test_http_response("200 OK", "hello world"),
+            ],
+        )
+        .await;
+        assert_eq!(contents, "hello world");
+        assert_eq!(range_header(&requests[1]), None);
+    }
+
+    #[tokio::test]
+    async fn test_download_resumable_truncates_oversized_partial_download() {
+        let (contents, requests) = download_with_responses(
+            Some("hello world, this is too long"),
+            11,
+            vec![test_http_response("200 OK", "hello world")],
+        )
+        .await;
+        assert_eq!(contents, "hello world");
+        assert_eq!(range_header(&requests[0]), None);
+    }
+
+    #[test]
+    fn test_get_partial_download_path() {
+        let path =
+            get_partial_download_path("/games/Titanfall2/cache/northstar-Northstar-1.0.0.zip");
+        assert_eq!(path.parent(), Some(std::env::temp_dir().as_path()));
+        assert_eq!(
+            path.file_name().unwrap(),
+            "flightcore-northstar-Northstar-1.0.0.zip.part"
+        );
+    }
+
+    #[test]
+    fn test_map_northstar_archive_entry() {
+        let entry = "Northstar/R2Northstar/mods/Northstar.Client/mod.json";
+        assert_eq!(
+            map_northstar_archive_entry(entry, NORTHSTAR_DEFAULT_PROFILE).as_deref(),
+            Some("R2Northstar/mods/Northstar.Client/mod.json")
+        );
+        assert_eq!(
+            map_northstar_archive_entry(entry, "R2Custom").as_deref(),
+            Some("R2Custom/mods/Northstar.Client/mod.json")
+        );
+        assert_eq!(
+            map_northstar_archive_entry("Northstar/Northstar.dll", "R2Custom").as_deref(),
+            Some("R2Custom/Northstar.dll")
+        );
+        assert_eq!(
+            map_northstar_archive_entry("Northstar/NorthstarLauncher.exe", "R2Custom").as_deref(),
+            Some("NorthstarLauncher.exe")
+        );
+        assert_eq!(map_northstar_archive_entry("README.md", "R2Custom"), None);
+    }
+
+    #[test]
+    fn test_check_northstar_archive_layout() {
+        let mut entry_names: Vec<String> = CORE_MODS
+            .iter()
+            .map(|core_mod| format!("Northstar/R2Northstar/mods/{core_mod}/mod.json"))
+            .collect();
+        entry_names.push("Northstar/NorthstarLauncher.exe".to_string());
+        entry_names.push("Northstar/R2Northstar/Northstar.dll".to_string());
+        assert!(check_northstar_archive_layout(&entry_names).is_empty());
+
+        // Thunderstore packages have their metadata at the root
+        for name in ["manifest.json", "icon.png", "README.md"] {
+            entry_names.push(name.to_string());
+        }
+        assert!(check_northstar_archive_layout(&entry_names).is_empty());
+
+        entry_names.retain(|name| !name.contains("Northstar.Custom/"));
+        entry_names.retain(|name| !name.ends_with("Northstar.dll"));
+        assert_eq!(
+            check_northstar_archive_layout(&entry_names),
+            vec![
+                "Core mod Northstar.Custom is missing",
+                "Northstar.dll is missing"
+            ]
+        );
+    }
+
+    /// Plans installing a release whose files can't be listed, as the stand-in server has none
+    async fn plan_release(game_install: &GameInstall) -> InstallPlan {
+        let (url, server) = serve_test_responses(vec![test_http_response("404 Not Found", "")]);
+        let plan = plan_northstar_release(
+            game_install,
+            "northstar-Northstar-1.30.0".to_string(),
+            &format!("{url}/northstar-Northstar-1.30.0.zip"),
+            1234,
+        )
+        .await;
+        server.join().unwrap();
+        plan
+    }
+
+    #[tokio::test]
+    async fn test_plan_northstar_release_install() {
+        let temp_directory = create_test_directory();
+        let game_install = GameInstall {
+            game_path: temp_directory.path().to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+
+        let plan = plan_release(&game_install).await;
+        assert_eq!(plan.packages, vec!["northstar-Northstar-1.30.0"]);
+        assert_eq!(plan.download_size, 1234);
+        assert!(plan.paths_to_delete.is_empty());
+        assert_eq!(
+            plan.paths_to_write,
+            vec!["NorthstarLauncher.exe", "R2Northstar"]
+        );
+        assert_eq!(plan.warnings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_plan_northstar_release_update() {
+        let temp_directory = create_test_directory();
+        let mods_directory = temp_directory.path().join("R2Northstar/mods");
+        for mod_name in ["Northstar.Client", "Northstar.Custom", "Some.Mod"] {
+            std::fs::create_dir_all(mods_directory.join(mod_name)).unwrap();
+            std::fs::write(mods_directory.join(mod_name).join("mod.json"), "{}").unwrap();
+        }
+        let game_install = GameInstall {
+            game_path: temp_directory.path().to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+
+        // Only the core mods that are installed get replaced, other mods are kept
+        let plan = plan_release(&game_install).await;
+        assert_eq!(
+            plan.paths_to_delete,
+            vec![
+                "R2Northstar/mods/Northstar.Client",
+                "R2Northstar/mods/Northstar.Custom"
+            ]
+        );
+        assert_eq!(plan.download_size, 1234);
+    }
+}
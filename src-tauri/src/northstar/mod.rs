@@ -1,7 +1,12 @@
 //! This module deals with handling things around Northstar such as
 //! - getting version number
+pub mod backup;
 pub mod install;
+pub mod preflight;
 pub mod profile;
+pub mod repair;
+pub mod uninstall;
+pub mod version;
 
 use crate::util::check_ea_app_or_origin_running;
 use crate::{constants::CORE_MODS, platform_specific::get_host_os, GameInstall, InstallType};
@@ -127,8 +132,7 @@ pub fn check_mod_version_number(path_to_mod_folder: &str) -> Result<String, anyh
 pub fn get_northstar_version_number(game_install: GameInstall) -> Result<String, String> {
     log::info!("{}", game_install.game_path);
 
-    // TODO:
-    // Check if NorthstarLauncher.exe exists and check its version number
+    // Launcher and DLL versions are reported separately by `version::get_northstar_component_versions`
     let initial_version_number = match check_mod_version_number(&format!(
         "{}/{}/mods/{}",
         game_install.game_path, game_install.profile, CORE_MODS[0]
@@ -155,6 +159,53 @@ pub fn get_northstar_version_number(game_install: GameInstall) -> Result<String,
     Ok(initial_version_number)
 }
 
+/// Arguments passed to Northstar in addition to the profile
+/// Launch arguments required by the profile's packages come first, followed by its ConVar overrides
+fn get_extra_launch_args(game_install: &GameInstall) -> Vec<String> {
+    let mut launch_args =
+        crate::mod_management::install_rules::get_profile_launch_args(&game_install.profile);
+    launch_args.extend(crate::mod_management::convars::get_convar_launch_args(
+        game_install,
+    ));
+    launch_args
+}
+
+/// Arguments for `cmd.exe` starting `NorthstarLauncher.exe` with the profile and extra arguments
+fn get_windows_launch_args(
+    game_install: &GameInstall,
+    extra_launch_args: &[String],
+) -> Vec<String> {
+    let mut args = vec![
+        "/C".to_string(),
+        "start".to_string(),
+        String::new(),
+        format!("{}/NorthstarLauncher.exe", game_install.game_path),
+        format!("-profile={}", game_install.profile),
+    ];
+    args.extend_from_slice(extra_launch_args);
+    args
+}
+
+/// Steam URL launching Northstar with the profile and extra arguments
+fn get_steam_launch_url(profile: &str, extra_launch_args: &[String]) -> String {
+    format!(
+        "steam://run/{}//-profile={} {}--northstar/",
+        thermite::TITANFALL2_STEAM_ID,
+        profile,
+        extra_launch_args
+            .iter()
+            .map(|arg| {
+                // Keep values containing spaces together
+                if arg.contains(char::is_whitespace) {
+                    format!("\"{arg}\" ")
+                } else {
+                    format!("{arg} ")
+                }
+            })
+            .collect::<String>()
+    )
+}
+
 /// Launches Northstar
 #[tauri::command]
 pub fn launch_northstar(
@@ -211,11 +262,10 @@ pub fn launch_northstar(
             || matches!(game_install.install_type, InstallType::ORIGIN)
             || matches!(game_install.install_type, InstallType::UNKNOWN))
     {
-        let ns_exe_path = format!("{}/NorthstarLauncher.exe", game_install.game_path);
-        let ns_profile_arg = format!("-profile={}", game_install.profile);
+        let extra_launch_args = get_extra_launch_args(&game_install);
 
         let mut output = std::process::Command::new("C:\\Windows\\System32\\cmd.exe")
-            .args(["/C", "start", "", &ns_exe_path, &ns_profile_arg])
+            .args(get_windows_launch_args(&game_install, &extra_launch_args))
             .spawn()
             .expect("failed to execute process");
         output.wait().expect("failed waiting on child process");
@@ -260,17 +310,90 @@ pub fn launch_northstar_steam(game_install: GameInstall) -> Result<String, Strin
     }
 
     // Switch to Titanfall2 directory to set everything up
-    if std::env::set_current_dir(game_install.game_path).is_err() {
+    if std::env::set_current_dir(&game_install.game_path).is_err() {
         // We failed to get to Titanfall2 directory
         return Err("Couldn't access Titanfall2 directory".to_string());
     }
 
-    match open::that(format!(
-        "steam://run/{}//-profile={} --northstar/",
-        thermite::TITANFALL2_STEAM_ID,
-        game_install.profile
+    let extra_launch_args = get_extra_launch_args(&game_install);
+
+    match open::that(get_steam_launch_url(
+        &game_install.profile,
+        &extra_launch_args,
     )) {
         Ok(()) => Ok("Started game".to_string()),
         Err(_err) => Err("Failed to launch Titanfall 2 via Steam".to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    fn vanilla_plus_install(game_path: &std::path::Path) -> GameInstall {
+        GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: "R2VanillaPlus".to_string(),
+            install_type: InstallType::STEAM,
+        }
+    }
+
+    #[test]
+    fn test_get_extra_launch_args() {
+        let temp_directory = create_test_directory();
+        let game_install = vanilla_plus_install(temp_directory.path());
+        // Launch arguments of the dedicated profile from the bundled install rules
+        assert_eq!(get_extra_launch_args(&game_install), vec!["-vanilla"]);
+
+        std::fs::create_dir_all(temp_directory.path().join("R2VanillaPlus")).unwrap();
+        crate::mod_management::convars::set_convar_override(
+            game_install.clone(),
+            "some_convar".to_string(),
+            Some("a b".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            get_extra_launch_args(&game_install),
+            vec!["-vanilla", "+some_convar", "a b"]
+        );
+    }
+
+    #[test]
+    fn test_get_windows_launch_args() {
+        let game_install = vanilla_plus_install(std::path::Path::new("C:/Titanfall2"));
+        let extra_launch_args = vec!["-vanilla".to_string(), "+some_convar".to_string()];
+        assert_eq!(
+            get_windows_launch_args(&game_install, &extra_launch_args),
+            vec![
+                "/C",
+                "start",
+                "",
+                "C:/Titanfall2/NorthstarLauncher.exe",
+                "-profile=R2VanillaPlus",
+                "-vanilla",
+                "+some_convar",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_steam_launch_url() {
+        assert_eq!(
+            get_steam_launch_url("R2Northstar", &[]),
+            format!(
+                "steam://run/{}//-profile=R2Northstar --northstar/",
+                thermite::TITANFALL2_STEAM_ID
+            )
+        );
+
+        let extra_launch_args = ["-vanilla", "+some_convar", "a b"].map(String::from);
+        assert_eq!(
+            get_steam_launch_url("R2VanillaPlus", &extra_launch_args),
+            format!(
+                "steam://run/{}//-profile=R2VanillaPlus -vanilla +some_convar \"a b\" --northstar/",
+                thermite::TITANFALL2_STEAM_ID
+            )
+        );
+    }
+}
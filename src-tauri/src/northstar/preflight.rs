@@ -0,0 +1,335 @@
+//! Checks run before installing Northstar to catch problems before anything is changed
+use crate::constants::{CORE_MODS, NORTHSTAR_DLL};
+use crate::util::{
+    check_northstar_running, get_files_recursively, get_folder_size, list_remote_zip_entries,
+};
+use crate::GameInstall;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Problem that would make installing Northstar fail
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export)]
+pub enum PreflightError {
+    /// Not enough free space on the volume the game is installed on
+    InsufficientDiskSpace {
+        required: u64,
+        available: u64,
+    },
+    /// No files can be created in the folder
+    NotWritable {
+        path: String,
+    },
+    /// File that has to be replaced is marked read-only
+    ReadOnlyFile {
+        path: String,
+    },
+    /// File that has to be replaced is in use by another process
+    LockedFile {
+        path: String,
+    },
+    NorthstarRunning,
+}
+
+/// Outcome of the checks run before installing Northstar
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export)]
+pub struct PreflightReport {
+    /// Problems that would make the install fail
+    pub errors: Vec<PreflightError>,
+    /// Whether files in use by other programs could be checked for
+    /// Only Windows prevents replacing files that are in use, so this is false elsewhere
+    pub lock_detection_available: bool,
+}
+
+impl std::fmt::Display for PreflightError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreflightError::InsufficientDiskSpace {
+                required,
+                available,
+            } => write!(
+                f,
+                "Not enough disk space, {} MB are required but only {} MB are available",
+                required / 1_000_000,
+                available / 1_000_000
+            ),
+            PreflightError::NotWritable { path } => {
+                write!(
+                    f,
+                    "Cannot write to {path}, check the folder's permissions. \
+                    Games installed by the EA App to C:\\Program Files\\EA Games\\Titanfall2 \
+                    usually have to be moved to a different folder first"
+                )
+            }
+            PreflightError::ReadOnlyFile { path } => write!(f, "{path} is read-only"),
+            PreflightError::LockedFile { path } => {
+                write!(f, "{path} is in use by another program")
+            }
+            PreflightError::NorthstarRunning => {
+                write!(f, "Northstar is running, close it before installing")
+            }
+        }
+    }
+}
+
+/// Returns the free space of the volume the path is on, if it can be determined
+fn get_available_space(path: &Path) -> Option<u64> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    // Most specific mount point containing the path
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().components().count())
+        .map(|disk| disk.available_space())
+}
+
+/// Checks whether files can be created in the folder by creating and removing one
+fn check_writable(directory: &Path) -> Option<PreflightError> {
+    let probe_path = directory.join(".flightcore-write-test");
+    let result = std::fs::write(&probe_path, b"").and_then(|()| std::fs::remove_file(&probe_path));
+    match result {
+        Ok(()) => None,
+        Err(err) => {
+            log::warn!("Failed writing to {}: {err}", directory.display());
+            Some(PreflightError::NotWritable {
+                path: directory.display().to_string(),
+            })
+        }
+    }
+}
+
+/// Checks whether an existing file can be replaced
+fn check_replaceable(path: &Path) -> Option<PreflightError> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.permissions().readonly() {
+        return Some(PreflightError::ReadOnlyFile {
+            path: path.display().to_string(),
+        });
+    }
+
+    // Opening for writing without truncating fails if another process has the file open
+    // Other systems allow replacing files that are in use, so there is nothing to detect
+    #[cfg(target_os = "windows")]
+    if let Err(err) = std::fs::OpenOptions::new().write(true).open(path) {
+        log::warn!("Failed opening {} for writing: {err}", path.display());
+        return Some(PreflightError::LockedFile {
+            path: path.display().to_string(),
+        });
+    }
+
+    None
+}
+
+/// Files of the current Northstar install that installing replaces
+fn get_files_to_replace(game_install: &GameInstall) -> Vec<PathBuf> {
+    let game_path = Path::new(&game_install.game_path);
+    let profile_path = game_path.join(&game_install.profile);
+
+    let mut files = vec![
+        game_path.join("NorthstarLauncher.exe"),
+        game_path.join(NORTHSTAR_DLL),
+        profile_path.join(NORTHSTAR_DLL),
+    ];
+    for core_mod in CORE_MODS {
+        files.extend(
+            get_files_recursively(profile_path.join("mods").join(core_mod)).unwrap_or_default(),
+        );
+    }
+    files.retain(|path| path.is_file());
+    files
+}
+
+/// Space needed for the downloaded archive, the extracted release and the backup of the
+/// current core mods
+fn get_required_space(game_install: &GameInstall, download_size: u64, extracted_size: u64) -> u64 {
+    let mods_path = Path::new(&game_install.game_path)
+        .join(&game_install.profile)
+        .join("mods");
+    let backup_size: u64 = CORE_MODS
+        .iter()
+        .map(|core_mod| get_folder_size(mods_path.join(core_mod)).unwrap_or_default())
+        .sum();
+    download_size + extracted_size + backup_size
+}
+
+/// Checks disk space, permissions and file locks before installing the release at `download_url`
+pub async fn check_northstar_install(
+    game_install: &GameInstall,
+    download_url: &str,
+    download_size: u64,
+) -> PreflightReport {
+    let mut errors = Vec::new();
+    let game_path = Path::new(&game_install.game_path);
+
+    if check_northstar_running() {
+        errors.push(PreflightError::NorthstarRunning);
+    }
+
+    // Release is downloaded and extracted into the game folder before the current install is
+    // backed up, so space for all three is needed at the same time
+    let extracted_size = match list_remote_zip_entries(download_url).await {
+        Ok(entries) => entries.iter().map(|entry| entry.uncompressed_size).sum(),
+        Err(err) => {
+            log::warn!("Failed getting extracted size of {download_url}: {err}");
+            download_size
+        }
+    };
+    let required = get_required_space(game_install, download_size, extracted_size);
+    match get_available_space(game_path) {
+        Some(available) if available < required => {
+            errors.push(PreflightError::InsufficientDiskSpace {
+                required,
+                available,
+            })
+        }
+        Some(_) => (),
+        None => log::warn!("Could not determine free space for {}", game_path.display()),
+    }
+
+    errors.extend(check_writable(game_path));
+    let profile_path = game_path.join(&game_install.profile);
+    if profile_path.is_dir() {
+        errors.extend(check_writable(&profile_path));
+    }
+
+    errors.extend(
+        get_files_to_replace(game_install)
+            .iter()
+            .filter_map(|path| check_replaceable(path)),
+    );
+
+    PreflightReport {
+        errors,
+        lock_detection_available: cfg!(target_os = "windows"),
+    }
+}
+
+/// Checks whether the given Northstar version can be installed
+/// Checks the latest version if no version is passed
+#[tauri::command]
+pub async fn preflight_check_northstar_install(
+    game_install: GameInstall,
+    northstar_package_name: Option<String>,
+    version_number: Option<String>,
+) -> Result<PreflightReport, String> {
+    let northstar_package_name = northstar_package_name
+        .filter(|name| name.len() > 1)
+        .unwrap_or("Northstar".to_string());
+
+    let index = match thermite::api::get_package_index() {
+        Ok(res) => res.to_vec(),
+        Err(err) => {
+            log::warn!("Failed fetching package index due to: {err}");
+            return Err("Failed to connect to Thunderstore.".to_string());
+        }
+    };
+    let nmod = index
+        .iter()
+        .find(|f| f.name.to_lowercase() == northstar_package_name.to_lowercase())
+        .ok_or_else(|| format!("Couldn't find {northstar_package_name} on Thunderstore"))?;
+    let version = version_number.as_ref().unwrap_or(&nmod.latest);
+    let nmod_version = nmod
+        .versions
+        .get(version)
+        .ok_or_else(|| format!("Couldn't find {northstar_package_name} {version}"))?;
+
+    Ok(check_northstar_install(&game_install, &nmod_version.url, nmod_version.file_size).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::NORTHSTAR_DEFAULT_PROFILE;
+    use crate::util::{create_test_directory, serve_test_responses, test_http_response};
+
+    #[test]
+    fn test_get_available_space() {
+        assert!(get_available_space(&std::env::temp_dir()).is_some());
+        // Relative paths aren't below any mount point
+        assert_eq!(get_available_space(Path::new("Titanfall2")), None);
+    }
+
+    #[test]
+    fn test_check_writable() {
+        let temp_directory = create_test_directory();
+        let directory = temp_directory.path();
+        assert_eq!(check_writable(directory), None);
+        assert!(!directory.join(".flightcore-write-test").exists());
+
+        let missing_directory = directory.join("missing");
+        assert_eq!(
+            check_writable(&missing_directory),
+            Some(PreflightError::NotWritable {
+                path: missing_directory.display().to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_replaceable() {
+        let temp_directory = create_test_directory();
+        let directory = temp_directory.path();
+        let path = directory.join(NORTHSTAR_DLL);
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(check_replaceable(&path), None);
+
+        let mut permissions = std::fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&path, permissions.clone()).unwrap();
+        assert_eq!(
+            check_replaceable(&path),
+            Some(PreflightError::ReadOnlyFile {
+                path: path.display().to_string()
+            })
+        );
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        std::fs::set_permissions(&path, permissions).unwrap();
+
+        // Files that don't exist yet don't need replacing
+        assert_eq!(check_replaceable(&directory.join("missing.dll")), None);
+    }
+
+    #[test]
+    fn test_get_required_space() {
+        let temp_directory = create_test_directory();
+        let directory = temp_directory.path();
+        let mods_path = directory.join(NORTHSTAR_DEFAULT_PROFILE).join("mods");
+        std::fs::create_dir_all(mods_path.join(CORE_MODS[0])).unwrap();
+        std::fs::write(mods_path.join(CORE_MODS[0]).join("mod.json"), [0; 100]).unwrap();
+        // Mods other than the core mods aren't backed up
+        std::fs::create_dir_all(mods_path.join("Example.Mod")).unwrap();
+        std::fs::write(mods_path.join("Example.Mod/mod.json"), [0; 1000]).unwrap();
+
+        let game_install = GameInstall {
+            game_path: directory.to_string_lossy().to_string(),
+            profile: NORTHSTAR_DEFAULT_PROFILE.to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        assert_eq!(get_required_space(&game_install, 10, 20), 130);
+    }
+
+    #[tokio::test]
+    async fn test_check_northstar_install() {
+        let temp_directory = create_test_directory();
+        let directory = temp_directory.path();
+        std::fs::create_dir_all(directory.join(NORTHSTAR_DEFAULT_PROFILE)).unwrap();
+        std::fs::write(directory.join("NorthstarLauncher.exe"), "").unwrap();
+        let game_install = GameInstall {
+            game_path: directory.to_string_lossy().to_string(),
+            profile: NORTHSTAR_DEFAULT_PROFILE.to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+
+        // Extracted size falls back to the download size if the release can't be listed
+        let (url, server) = serve_test_responses(vec![test_http_response("404 Not Found", "")]);
+        let report = check_northstar_install(&game_install, &format!("{url}/release.zip"), 1).await;
+        server.join().unwrap();
+
+        assert_eq!(report.errors, vec![]);
+        assert_eq!(report.lock_detection_available, cfg!(target_os = "windows"));
+    }
+}
@@ -1,10 +1,16 @@
-use crate::util::copy_dir_all;
+use crate::constants::{NORTHSTAR_ARCHIVE_CACHE_DIR, NORTHSTAR_BACKUP_DIR};
+use crate::mod_management::get_installed_mods_and_properties;
+use crate::util::{copy_dir_all, get_folder_size};
 use crate::GameInstall;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 // These folders are part of Titanfall 2 and
 // should NEVER be used as a Profile
-const SKIP_PATHS: [&str; 8] = [
+const SKIP_PATHS: [&str; 10] = [
     "___flightcore-temp",
+    NORTHSTAR_ARCHIVE_CACHE_DIR,
+    NORTHSTAR_BACKUP_DIR,
     "__overlay",
     "bin",
     "Core",
@@ -28,6 +34,36 @@ const MAY_CONTAIN: [&str; 10] = [
     "LEGAL.txt",
 ];
 
+// Folders inside a profile that are included in disk usage reports
+const DISK_USAGE_FOLDERS: [&str; 5] = ["packages", "mods", "plugins", "logs", "runtime"];
+
+/// Size in bytes of a single named item, e.g. a folder, package or mod
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct DiskUsageEntry {
+    name: String,
+    size: u64,
+}
+
+/// Breakdown of how much disk space a profile takes up
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct ProfileDiskUsage {
+    profile: String,
+    total_size: u64,
+    folders: Vec<DiskUsageEntry>,
+    packages: Vec<DiskUsageEntry>,
+    mods: Vec<DiskUsageEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+struct DiskUsageProgress {
+    current: String,
+    done: u32,
+    total: u32,
+}
+
 /// Returns a list of Profile names
 /// All the returned Profiles can be found relative to the game path
 #[tauri::command]
@@ -119,3 +155,231 @@ pub fn clone_profile(
 
     Ok(())
 }
+
+/// Returns sizes of all direct subfolders of the given folder
+fn get_subfolder_sizes(path: &std::path::Path) -> Vec<DiskUsageEntry> {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut sizes: Vec<DiskUsageEntry> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| DiskUsageEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            size: get_folder_size(entry.path()).unwrap_or(0),
+        })
+        .collect();
+
+    // Largest first
+    sizes.sort_by_key(|entry| std::cmp::Reverse(entry.size));
+    sizes
+}
+
+/// Walks the folders of a profile and collects their sizes grouped by package and mod
+fn calculate_profile_disk_usage(
+    game_install: &GameInstall,
+    on_progress: impl Fn(DiskUsageProgress),
+) -> Result<ProfileDiskUsage, String> {
+    let profile_path =
+        std::path::PathBuf::from(&game_install.game_path).join(&game_install.profile);
+
+    let mut folders = Vec::new();
+    for (i, folder) in DISK_USAGE_FOLDERS.iter().enumerate() {
+        on_progress(DiskUsageProgress {
+            current: folder.to_string(),
+            done: i as u32,
+            total: DISK_USAGE_FOLDERS.len() as u32,
+        });
+
+        let folder_path = profile_path.join(folder);
+        if !folder_path.is_dir() {
+            continue;
+        }
+
+        folders.push(DiskUsageEntry {
+            name: folder.to_string(),
+            size: get_folder_size(&folder_path).map_err(|err| err.to_string())?,
+        });
+    }
+
+    let packages = get_subfolder_sizes(&profile_path.join("packages"));
+
+    let installed_mods = match get_installed_mods_and_properties(game_install.clone()) {
+        Ok(installed_mods) => installed_mods,
+        Err(err) => {
+            log::warn!("Couldn't get installed mods: {err}");
+            vec![]
+        }
+    };
+    let mut mods: Vec<DiskUsageEntry> = installed_mods
+        .into_iter()
+        .map(|ns_mod| DiskUsageEntry {
+            size: get_folder_size(&ns_mod.directory).unwrap_or(0),
+            name: ns_mod.name,
+        })
+        .collect();
+    mods.sort_by_key(|entry| std::cmp::Reverse(entry.size));
+
+    Ok(ProfileDiskUsage {
+        profile: game_install.profile.clone(),
+        total_size: folders.iter().map(|folder| folder.size).sum(),
+        folders,
+        packages,
+        mods,
+    })
+}
+
+/// Returns how much disk space the folders, packages and mods of the active profile take up
+#[tauri::command]
+pub async fn get_profile_disk_usage(
+    window: tauri::Window,
+    game_install: GameInstall,
+) -> Result<ProfileDiskUsage, String> {
+    // Walking large profiles takes a while, so do it off the main thread
+    tauri::async_runtime::spawn_blocking(move || {
+        calculate_profile_disk_usage(&game_install, |progress| {
+            window
+                .emit("profile-disk-usage-progress", progress)
+                .unwrap()
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Sums up the size of every profile of the game install
+fn calculate_all_profiles_disk_usage(
+    game_install: &GameInstall,
+    on_progress: impl Fn(DiskUsageProgress),
+) -> Result<Vec<DiskUsageEntry>, String> {
+    let profiles = fetch_profiles(game_install.clone())?;
+
+    let mut sizes = Vec::new();
+    for (i, profile) in profiles.iter().enumerate() {
+        on_progress(DiskUsageProgress {
+            current: profile.clone(),
+            done: i as u32,
+            total: profiles.len() as u32,
+        });
+
+        let profile_path = format!("{}/{}", game_install.game_path, profile);
+        sizes.push(DiskUsageEntry {
+            name: profile.clone(),
+            size: get_folder_size(profile_path).map_err(|err| err.to_string())?,
+        });
+    }
+
+    Ok(sizes)
+}
+
+/// Returns the total size of every profile
+#[tauri::command]
+pub async fn get_all_profiles_disk_usage(
+    window: tauri::Window,
+    game_install: GameInstall,
+) -> Result<Vec<DiskUsageEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        calculate_all_profiles_disk_usage(&game_install, |progress| {
+            window
+                .emit("profile-disk-usage-progress", progress)
+                .unwrap()
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+    use std::path::Path;
+
+    fn write_file(path: &Path, size: usize) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, vec![0; size]).unwrap();
+    }
+
+    fn sizes(entries: &[DiskUsageEntry]) -> Vec<(&str, u64)> {
+        entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.size))
+            .collect()
+    }
+
+    #[test]
+    fn test_calculate_profile_disk_usage() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let profile = game_path.join("R2Northstar");
+        write_file(
+            &profile.join("packages/Tester-Small-1.0.0/manifest.json"),
+            10,
+        );
+        write_file(&profile.join("packages/Tester-Large-1.0.0/mods/a.vpk"), 300);
+        write_file(&profile.join("mods/Some.Mod/mod/scripts/a.nut"), 80);
+        // 20 bytes
+        std::fs::write(
+            profile.join("mods/Some.Mod/mod.json"),
+            r#"{"Name": "Some.Mod"}"#,
+        )
+        .unwrap();
+        write_file(&profile.join("logs/nslog.txt"), 5);
+        // Not part of any reported folder
+        write_file(&profile.join("Northstar.dll"), 1000);
+        write_file(&profile.join("save_data/settings.json"), 1000);
+
+        let game_install = GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        let progress = std::cell::RefCell::new(Vec::new());
+        let disk_usage = calculate_profile_disk_usage(&game_install, |update| {
+            progress.borrow_mut().push(update.current)
+        })
+        .unwrap();
+
+        assert_eq!(progress.into_inner(), DISK_USAGE_FOLDERS);
+        assert_eq!(
+            sizes(&disk_usage.folders),
+            vec![("packages", 310), ("mods", 100), ("logs", 5)]
+        );
+        assert_eq!(disk_usage.total_size, 415);
+        // Largest first
+        assert_eq!(
+            sizes(&disk_usage.packages),
+            vec![("Tester-Large-1.0.0", 300), ("Tester-Small-1.0.0", 10)]
+        );
+        assert_eq!(sizes(&disk_usage.mods), vec![("Some.Mod", 100)]);
+    }
+
+    #[test]
+    fn test_calculate_all_profiles_disk_usage() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        write_file(&game_path.join("R2Northstar/mods/Some.Mod/mod.json"), 20);
+        write_file(&game_path.join("R2Northstar/Northstar.dll"), 100);
+        write_file(&game_path.join("R2Other/enabledmods.json"), 2);
+        // Not a profile as it holds none of the files a profile has
+        write_file(
+            &game_path.join("vpk/client_frontend.bsp.pak000_dir.vpk"),
+            1000,
+        );
+
+        let game_install = GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        let mut disk_usage = calculate_all_profiles_disk_usage(&game_install, |_| ()).unwrap();
+        disk_usage.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(
+            sizes(&disk_usage),
+            vec![("R2Northstar", 120), ("R2Other", 2)]
+        );
+    }
+}
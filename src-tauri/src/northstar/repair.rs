@@ -0,0 +1,334 @@
+//! Restoring individual core mods from the matching Northstar release without a full reinstall
+use crate::constants::{CORE_MODS, NORTHSTAR_ARCHIVE_CACHE_DIR, NORTHSTAR_DEFAULT_PROFILE};
+use crate::northstar::install::{
+    check_northstar_archive_layout, download_northstar_release, get_cached_archive_path,
+};
+use crate::northstar::version::{normalize_version, read_pe_file_version};
+use crate::northstar::{check_mod_version_number, uninstall};
+use crate::util::{
+    check_northstar_running, convert_release_candidate_number, get_relative_files_recursively,
+    move_dir_all,
+};
+use crate::GameInstall;
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// Picks the version the core mods should be at
+///
+/// The version most core mods agree on wins, ties are settled by the launcher version which is
+/// installed together with the core mods. Falls back to the launcher version if no core mod has one.
+fn get_expected_version(
+    core_mod_versions: &[Option<String>],
+    launcher_version: Option<&str>,
+) -> Option<String> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for version in core_mod_versions.iter().flatten() {
+        *counts.entry(version).or_default() += 1;
+    }
+
+    let matches_launcher = |version: &str| {
+        launcher_version.is_some_and(|launcher_version| {
+            normalize_version(launcher_version) == normalize_version(version)
+        })
+    };
+    counts
+        .into_iter()
+        .max_by_key(|(version, count)| (*count, matches_launcher(version)))
+        .map(|(version, _)| version.to_string())
+        .or_else(|| launcher_version.map(str::to_string))
+}
+
+/// Reads the version of the first core mod inside a release archive
+fn read_archive_version(path: &Path) -> Result<String> {
+    let mut archive = ZipArchive::new(std::fs::File::open(path)?)?;
+    let mut mod_json = String::new();
+    archive
+        .by_name(&format!(
+            "Northstar/{NORTHSTAR_DEFAULT_PROFILE}/mods/{}/mod.json",
+            CORE_MODS[0]
+        ))?
+        .read_to_string(&mut mod_json)?;
+
+    let parsed_json: serde_json::Value = serde_json::from_str(&mod_json)?;
+    match parsed_json.get("Version").and_then(|value| value.as_str()) {
+        Some(version_number) => Ok(version_number.to_string()),
+        None => Err(anyhow!("No version number found")),
+    }
+}
+
+/// Finds a cached release archive of the given version
+fn find_cached_archive(game_install: &GameInstall, version: &str) -> Option<PathBuf> {
+    let cache_directory = Path::new(&game_install.game_path).join(NORTHSTAR_ARCHIVE_CACHE_DIR);
+    std::fs::read_dir(cache_directory)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| match read_archive_version(path) {
+            Ok(archive_version) => archive_version == version,
+            Err(err) => {
+                log::warn!("Failed reading cached archive {}: {err}", path.display());
+                false
+            }
+        })
+}
+
+/// Returns the release archive of the given version, downloading it if it isn't cached
+async fn get_release_archive(
+    window: &tauri::Window,
+    game_install: &GameInstall,
+    version: &str,
+) -> Result<std::fs::File> {
+    if let Some(path) = find_cached_archive(game_install, version) {
+        log::info!("Using cached archive {}", path.display());
+        return Ok(std::fs::File::open(path)?);
+    }
+
+    // Release candidate version numbers are different between `mod.json` and Thunderstore
+    let package_name = if version.contains("-rc") {
+        "NorthstarReleaseCandidate"
+    } else {
+        "Northstar"
+    };
+    let thunderstore_version = convert_release_candidate_number(version.to_string());
+
+    let index = thermite::api::get_package_index().context("Failed to connect to Thunderstore")?;
+    let nmod = index
+        .iter()
+        .find(|f| f.name.to_lowercase() == package_name.to_lowercase())
+        .and_then(|f| f.versions.get(&thunderstore_version))
+        .ok_or_else(|| anyhow!("Couldn't find {package_name} {thunderstore_version}"))?;
+
+    std::fs::create_dir_all(Path::new(&game_install.game_path).join(NORTHSTAR_ARCHIVE_CACHE_DIR))?;
+    download_northstar_release(window, nmod, &get_cached_archive_path(game_install, nmod)).await
+}
+
+/// Replaces the given core mods of the profile with the ones from the release archive
+fn restore_core_mods(
+    archive: std::fs::File,
+    game_install: &GameInstall,
+    core_mods: &[&str],
+) -> Result<()> {
+    let mut archive = ZipArchive::new(archive).context("Unable to open zip archive")?;
+    let entry_names: Vec<String> = archive.file_names().map(str::to_string).collect();
+    let problems = check_northstar_archive_layout(&entry_names);
+    if !problems.is_empty() {
+        return Err(anyhow!(
+            "Not a valid Northstar release: {}",
+            problems.join(", ")
+        ));
+    }
+
+    // Extract next to the install first so broken core mods are only removed once the
+    // replacement is complete
+    let temp_dir = Path::new(&game_install.game_path).join("___flightcore-temp");
+    let extract_directory = temp_dir.join("repair-dir");
+    let archive_mods_directory = Path::new("Northstar")
+        .join(NORTHSTAR_DEFAULT_PROFILE)
+        .join("mods");
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(path) = entry.enclosed_name().map(|path| path.to_path_buf()) else {
+            continue;
+        };
+        let Ok(relative_path) = path.strip_prefix(&archive_mods_directory) else {
+            continue;
+        };
+        if entry.is_dir()
+            || !core_mods
+                .iter()
+                .any(|core_mod| relative_path.starts_with(core_mod))
+        {
+            continue;
+        }
+
+        let out = extract_directory.join(relative_path);
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent).context("Unable to create directory")?;
+        }
+        log::info!("Write file {}", out.display());
+        std::io::copy(&mut entry, &mut std::fs::File::create(&out)?)
+            .context("Unable to write to file")?;
+    }
+
+    let mods_directory = Path::new(&game_install.game_path)
+        .join(&game_install.profile)
+        .join("mods");
+    std::fs::create_dir_all(&mods_directory)?;
+    let mut restored_files = Vec::new();
+    for core_mod in core_mods {
+        let mod_directory = mods_directory.join(core_mod);
+        if mod_directory.exists() {
+            std::fs::remove_dir_all(&mod_directory)?;
+        }
+        log::info!("Restoring {core_mod}");
+        move_dir_all(extract_directory.join(core_mod), &mod_directory)?;
+
+        let relative_mod_directory = Path::new(&game_install.profile).join("mods").join(core_mod);
+        restored_files.extend(
+            get_relative_files_recursively(&mod_directory)?
+                .into_iter()
+                .map(|path| relative_mod_directory.join(path)),
+        );
+    }
+
+    // Restored files no longer match the hashes recorded by the previous install
+    if let Err(err) = uninstall::record_installed_files(game_install, &restored_files) {
+        log::warn!("Failed writing Northstar install manifest due to: {err}");
+    }
+
+    std::fs::remove_dir_all(temp_dir)?;
+    Ok(())
+}
+
+async fn repair(window: &tauri::Window, game_install: &GameInstall) -> Result<Vec<String>> {
+    let core_mod_versions: Vec<Option<String>> = CORE_MODS
+        .iter()
+        .map(|core_mod| {
+            check_mod_version_number(&format!(
+                "{}/{}/mods/{}",
+                game_install.game_path, game_install.profile, core_mod
+            ))
+            .ok()
+        })
+        .collect();
+    let launcher_version =
+        read_pe_file_version(&Path::new(&game_install.game_path).join("NorthstarLauncher.exe"));
+
+    let expected_version = get_expected_version(&core_mod_versions, launcher_version.as_deref())
+        .ok_or_else(|| {
+            anyhow!("Couldn't determine the installed Northstar version, reinstall Northstar")
+        })?;
+    let broken_core_mods: Vec<&str> = CORE_MODS
+        .iter()
+        .zip(&core_mod_versions)
+        .filter(|(_, version)| version.as_deref() != Some(expected_version.as_str()))
+        .map(|(core_mod, _)| *core_mod)
+        .collect();
+
+    if broken_core_mods.is_empty() {
+        log::info!("All core mods are at version {expected_version}");
+        return Ok(Vec::new());
+    }
+    log::info!(
+        "Restoring {} from Northstar {expected_version}",
+        broken_core_mods.join(", ")
+    );
+
+    let archive = get_release_archive(window, game_install, &expected_version).await?;
+    restore_core_mods(archive, game_install, &broken_core_mods)?;
+
+    Ok(broken_core_mods
+        .iter()
+        .map(|core_mod| core_mod.to_string())
+        .collect())
+}
+
+/// Restores core mods that are missing or at a different version than the rest of the install
+/// Returns the names of the restored core mods, empty if nothing needed repairing
+#[tauri::command]
+pub async fn repair_core_mods(
+    window: tauri::Window,
+    game_install: GameInstall,
+) -> Result<Vec<String>, String> {
+    if check_northstar_running() {
+        return Err("Northstar is running, close it before repairing".to_string());
+    }
+
+    match repair(&window, &game_install).await {
+        Ok(repaired_core_mods) => {
+            log::info!("Done repairing core mods");
+            Ok(repaired_core_mods)
+        }
+        Err(err) => {
+            let error_message = format!("Failed repairing core mods: {err:#}");
+            log::error!("{error_message}");
+            Err(error_message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    #[test]
+    fn test_get_expected_version() {
+        let versions = [Some("1.24.0".to_string()), None, Some("1.24.0".to_string())];
+        assert_eq!(
+            get_expected_version(&versions, Some("1.23.0")),
+            Some("1.24.0".to_string())
+        );
+
+        // Launcher settles ties
+        let versions = [Some("1.23.0".to_string()), Some("1.24.0".to_string()), None];
+        assert_eq!(
+            get_expected_version(&versions, Some("1.24.0")),
+            Some("1.24.0".to_string())
+        );
+
+        assert_eq!(
+            get_expected_version(&[None, None, None], Some("1.24.0")),
+            Some("1.24.0".to_string())
+        );
+        assert_eq!(get_expected_version(&[None, None, None], None), None);
+    }
+
+    #[test]
+    fn test_restore_core_mods() {
+        use std::io::Write;
+
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let mods_directory = game_path.join(NORTHSTAR_DEFAULT_PROFILE).join("mods");
+        std::fs::create_dir_all(mods_directory.join("Northstar.Custom")).unwrap();
+        std::fs::write(mods_directory.join("Northstar.Custom/stale.nut"), "").unwrap();
+
+        // Laid out like the release on Thunderstore
+        let archive_path = game_path.join("release.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+        let options = zip::write::FileOptions::default();
+        for name in ["manifest.json", "icon.png", "README.md"] {
+            zip.start_file(name, options).unwrap();
+        }
+        zip.start_file("Northstar/R2Northstar/Northstar.dll", options)
+            .unwrap();
+        for core_mod in CORE_MODS {
+            zip.start_file(
+                format!("Northstar/R2Northstar/mods/{core_mod}/mod.json"),
+                options,
+            )
+            .unwrap();
+            zip.write_all(core_mod.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let game_install = GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: NORTHSTAR_DEFAULT_PROFILE.to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        restore_core_mods(
+            std::fs::File::open(&archive_path).unwrap(),
+            &game_install,
+            &["Northstar.Custom"],
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(mods_directory.join("Northstar.Custom/mod.json")).unwrap(),
+            "Northstar.Custom"
+        );
+        assert!(!mods_directory.join("Northstar.Custom/stale.nut").exists());
+        // Only the requested core mods are restored
+        assert!(!mods_directory.join("Northstar.Client").exists());
+        assert!(!game_path.join("___flightcore-temp").exists());
+        assert_eq!(
+            uninstall::get_recorded_files(&game_install),
+            vec!["R2Northstar/mods/Northstar.Custom/mod.json"]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Recording which files a Northstar install placed into the game folder and removing them again
+use crate::constants::{
+    NORTHSTAR_ARCHIVE_CACHE_DIR, NORTHSTAR_BACKUP_DIR, NORTHSTAR_DLL, NORTHSTAR_INSTALL_MANIFEST,
+};
+use crate::northstar::profile::{fetch_profiles, validate_profile};
+use crate::util::{check_northstar_running, sha256_file};
+use crate::GameInstall;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tauri::api::dialog::blocking::MessageDialogBuilder;
+use tauri::api::dialog::{MessageDialogButtons, MessageDialogKind};
+
+/// Files placed into the game folder by Northstar installs and their hashes
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct NorthstarInstallManifest {
+    /// Maps path relative to the game folder to SHA-256 hash
+    files: BTreeMap<String, String>,
+}
+
+fn read_install_manifest(game_path: &Path) -> Result<NorthstarInstallManifest> {
+    let data = std::fs::read_to_string(game_path.join(NORTHSTAR_INSTALL_MANIFEST))?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+/// Returns the files, relative to the game folder, recorded as installed by Northstar
+pub fn get_recorded_files(game_install: &GameInstall) -> Vec<String> {
+    read_install_manifest(Path::new(&game_install.game_path))
+        .map(|manifest| manifest.files.into_keys().collect())
+        .unwrap_or_default()
+}
+
+/// Adds the given files, relative to the game folder, to the install manifest
+///
+/// Installs into different profiles share the launcher, so files are merged into the
+/// existing manifest instead of replacing it.
+pub fn record_installed_files(game_install: &GameInstall, files: &[PathBuf]) -> Result<()> {
+    let game_path = Path::new(&game_install.game_path);
+    let mut manifest = read_install_manifest(game_path).unwrap_or_default();
+
+    for path in files {
+        // Always use forward slashes so manifests are the same across platforms
+        let relative_path = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        manifest
+            .files
+            .insert(relative_path, sha256_file(game_path.join(path))?);
+    }
+
+    std::fs::write(
+        game_path.join(NORTHSTAR_INSTALL_MANIFEST),
+        serde_json::to_string_pretty(&manifest)?,
+    )
+    .context("Failed writing Northstar install manifest")?;
+
+    Ok(())
+}
+
+/// Removes now empty folders from the folder of the given file upwards, stopping at the game folder
+fn remove_empty_parents(game_path: &Path, file_path: &Path) {
+    let mut directory = file_path.parent();
+    while let Some(path) = directory {
+        if path == game_path || std::fs::remove_dir(path).is_err() {
+            break;
+        }
+        directory = path.parent();
+    }
+}
+
+/// Profiles that certainly belong to Northstar, as opposed to any folder next to the game
+///
+/// These are the ones Northstar was installed into or that Northstar has been run with.
+fn get_northstar_profiles(
+    game_install: &GameInstall,
+    manifest: &NorthstarInstallManifest,
+) -> BTreeSet<String> {
+    let game_path = Path::new(&game_install.game_path);
+    let recorded_profiles: BTreeSet<&str> = manifest
+        .files
+        .keys()
+        .filter_map(|path| path.split_once('/').map(|(first, _)| first))
+        .collect();
+
+    fetch_profiles(game_install.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|profile| validate_profile(game_install.clone(), profile.clone()))
+        .filter(|profile| {
+            let profile_path = game_path.join(profile);
+            recorded_profiles.contains(profile.as_str())
+                || profile_path.join(NORTHSTAR_DLL).is_file()
+                || profile_path.join("enabledmods.json").is_file()
+        })
+        .collect()
+}
+
+/// Asks the user to confirm deleting the given profiles
+fn confirm_profile_removal(profiles: &BTreeSet<String>) -> bool {
+    let profile_list: Vec<&str> = profiles.iter().map(String::as_str).collect();
+    let dialog = MessageDialogBuilder::new(
+        "Uninstall Northstar",
+        format!(
+            "The following profiles, including their mods and saved data, will be deleted:\n\n{}\n\nPress 'Ok' to continue or 'Cancel' to abort uninstalling",
+            profile_list.join("\n")
+        ),
+    )
+    .kind(MessageDialogKind::Warning)
+    .buttons(MessageDialogButtons::OkCancel);
+
+    dialog.show()
+}
+
+/// Removes the recorded files, `confirm_removal` is asked before deleting any profiles
+fn uninstall(
+    game_install: &GameInstall,
+    keep_profiles: bool,
+    confirm_removal: impl FnOnce(&BTreeSet<String>) -> bool,
+) -> Result<Vec<String>> {
+    let game_path = Path::new(&game_install.game_path);
+    let manifest = read_install_manifest(game_path).context(
+        "No record of installed Northstar files found. Reinstall Northstar with FlightCore first",
+    )?;
+
+    let profiles = get_northstar_profiles(game_install, &manifest);
+    if !keep_profiles && !profiles.is_empty() && !confirm_removal(&profiles) {
+        return Err(anyhow!("Uninstall cancelled"));
+    }
+
+    let mut kept_files = Vec::new();
+    for (relative_path, expected_hash) in &manifest.files {
+        let in_profile = relative_path
+            .split('/')
+            .next()
+            .is_some_and(|first| profiles.contains(first));
+        if keep_profiles && in_profile {
+            continue;
+        }
+
+        let file_path = game_path.join(relative_path);
+        if !file_path.is_file() {
+            continue;
+        }
+
+        // Files changed since install may have been replaced by the game itself
+        if !in_profile && sha256_file(&file_path)? != *expected_hash {
+            log::warn!("{relative_path} was modified since install, keeping it");
+            kept_files.push(relative_path.clone());
+            continue;
+        }
+
+        log::info!("Removing {relative_path}");
+        std::fs::remove_file(&file_path)?;
+        remove_empty_parents(game_path, &file_path);
+    }
+
+    if !keep_profiles {
+        for profile in &profiles {
+            log::info!("Removing Profile {profile}");
+            std::fs::remove_dir_all(game_path.join(profile))?;
+        }
+    }
+
+    for directory in [NORTHSTAR_BACKUP_DIR, NORTHSTAR_ARCHIVE_CACHE_DIR] {
+        let directory = game_path.join(directory);
+        if directory.exists() {
+            std::fs::remove_dir_all(directory)?;
+        }
+    }
+    std::fs::remove_file(game_path.join(NORTHSTAR_INSTALL_MANIFEST))?;
+
+    Ok(kept_files)
+}
+
+/// Removes all files installed by Northstar, leaving a vanilla Titanfall 2 install
+/// Northstar profiles are removed as well after asking for confirmation unless `keep_profiles` is set
+/// Returns the files that were kept as they changed since they were installed
+#[tauri::command]
+pub async fn uninstall_northstar(
+    game_install: GameInstall,
+    keep_profiles: bool,
+) -> Result<Vec<String>, String> {
+    if check_northstar_running() {
+        return Err("Northstar is running, close it before uninstalling".to_string());
+    }
+
+    log::info!("Uninstalling Northstar from {}", game_install.game_path);
+    match uninstall(&game_install, keep_profiles, confirm_profile_removal) {
+        Ok(kept_files) => {
+            log::info!("Done uninstalling Northstar");
+            Ok(kept_files)
+        }
+        Err(err) => {
+            let error_message = format!("Failed uninstalling Northstar: {err:#}");
+            log::error!("{error_message}");
+            Err(error_message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::NORTHSTAR_DEFAULT_PROFILE;
+    use crate::util::create_test_directory;
+
+    const NORTHSTAR_FILES: [&str; 4] = [
+        "NorthstarLauncher.exe",
+        "bin/x64_retail/wsock32.dll",
+        "R2Northstar/Northstar.dll",
+        "R2Northstar/mods/Northstar.Client/mod.json",
+    ];
+
+    /// Lays out a game folder with vanilla files and a recorded Northstar install
+    fn create_northstar_install(game_path: &Path) -> GameInstall {
+        for path in NORTHSTAR_FILES
+            .iter()
+            .chain(&["Titanfall2.exe", "bin/x64_retail/engine.dll"])
+        {
+            let path = game_path.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::create_dir_all(game_path.join(NORTHSTAR_BACKUP_DIR)).unwrap();
+
+        let game_install = GameInstall {
+            game_path: game_path.to_string_lossy().to_string(),
+            profile: NORTHSTAR_DEFAULT_PROFILE.to_string(),
+            install_type: crate::InstallType::UNKNOWN,
+        };
+        let files: Vec<PathBuf> = NORTHSTAR_FILES.iter().map(PathBuf::from).collect();
+        record_installed_files(&game_install, &files).unwrap();
+        game_install
+    }
+
+    #[test]
+    fn test_uninstall() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let game_install = create_northstar_install(game_path);
+        std::fs::write(game_path.join("NorthstarLauncher.exe"), "modified").unwrap();
+
+        let kept_files = uninstall(&game_install, false, |profiles| {
+            assert_eq!(profiles, &BTreeSet::from(["R2Northstar".to_string()]));
+            true
+        })
+        .unwrap();
+
+        // Modified files are kept, unmodified ones removed
+        assert_eq!(kept_files, vec!["NorthstarLauncher.exe"]);
+        assert!(game_path.join("NorthstarLauncher.exe").exists());
+        assert!(!game_path.join("bin/x64_retail/wsock32.dll").exists());
+        assert!(!game_path.join("R2Northstar").exists());
+        assert!(!game_path.join(NORTHSTAR_BACKUP_DIR).exists());
+        assert!(!game_path.join(NORTHSTAR_INSTALL_MANIFEST).exists());
+        // Vanilla files are left alone
+        assert!(game_path.join("Titanfall2.exe").exists());
+        assert!(game_path.join("bin/x64_retail/engine.dll").exists());
+    }
+
+    #[test]
+    fn test_uninstall_keep_profiles() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let game_install = create_northstar_install(game_path);
+        std::fs::write(game_path.join("R2Northstar/enabledmods.json"), "{}").unwrap();
+
+        let kept_files = uninstall(&game_install, true, |_| {
+            panic!("Profiles are kept, nothing to confirm")
+        })
+        .unwrap();
+
+        assert!(kept_files.is_empty());
+        assert!(!game_path.join("NorthstarLauncher.exe").exists());
+        assert!(!game_path.join("bin/x64_retail/wsock32.dll").exists());
+        for path in [
+            "R2Northstar/Northstar.dll",
+            "R2Northstar/mods/Northstar.Client/mod.json",
+            "R2Northstar/enabledmods.json",
+        ] {
+            assert!(game_path.join(path).exists(), "{path} was removed");
+        }
+    }
+
+    #[test]
+    fn test_uninstall_cancelled() {
+        let temp_directory = create_test_directory();
+        let game_path = temp_directory.path();
+        let game_install = create_northstar_install(game_path);
+
+        assert!(uninstall(&game_install, false, |_| false).is_err());
+        for path in NORTHSTAR_FILES {
+            assert!(game_path.join(path).exists(), "{path} was removed");
+        }
+        assert!(game_path.join(NORTHSTAR_INSTALL_MANIFEST).exists());
+    }
+}
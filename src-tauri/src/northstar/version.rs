@@ -0,0 +1,257 @@
+//! Reading the versions of the individual parts of a Northstar install
+//!
+//! Launcher and DLL versions are taken from the version resource embedded in the PE file,
+//! which is parsed by hand so that this also works on Linux.
+use crate::constants::{CORE_MODS, NORTHSTAR_DLL};
+use crate::northstar::check_mod_version_number;
+use crate::GameInstall;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use ts_rs::TS;
+
+/// Resource type ID of version information
+const RT_VERSION: u32 = 16;
+
+/// Signature at the start of `VS_FIXEDFILEINFO`
+const VS_FIXEDFILEINFO_SIGNATURE: u32 = 0xFEEF04BD;
+
+/// Version of a single core mod
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct CoreModVersion {
+    name: String,
+    version: Option<String>,
+}
+
+/// Versions of the parts making up a Northstar install, `None` if a part is missing
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct NorthstarComponentVersions {
+    launcher_version: Option<String>,
+    dll_version: Option<String>,
+    core_mod_versions: Vec<CoreModVersion>,
+    /// Set if the parts are not all of the same version
+    mismatch: bool,
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/// Reads the file version from a `VS_VERSIONINFO` structure
+fn parse_version_info(data: &[u8]) -> Option<[u16; 4]> {
+    // Header consists of length, value length and type followed by the key `VS_VERSION_INFO`
+    // as null-terminated UTF-16, padded to 32 bits
+    let key: Vec<u16> = "VS_VERSION_INFO\0".encode_utf16().collect();
+    let key_offset = 6;
+    for (i, character) in key.iter().enumerate() {
+        if read_u16(data, key_offset + i * 2)? != *character {
+            return None;
+        }
+    }
+    let fixed_info_offset = (key_offset + key.len() * 2 + 3) & !3;
+
+    if read_u32(data, fixed_info_offset)? != VS_FIXEDFILEINFO_SIGNATURE {
+        return None;
+    }
+    let version_ms = read_u32(data, fixed_info_offset + 8)?;
+    let version_ls = read_u32(data, fixed_info_offset + 12)?;
+    Some([
+        (version_ms >> 16) as u16,
+        version_ms as u16,
+        (version_ls >> 16) as u16,
+        version_ls as u16,
+    ])
+}
+
+/// Finds the data of the first resource of the given type in a PE file
+fn find_resource(data: &[u8], resource_type: u32) -> Option<&[u8]> {
+    // DOS header points to the PE header
+    if data.get(0..2)? != b"MZ" {
+        return None;
+    }
+    let pe_offset = read_u32(data, 0x3C)? as usize;
+    if data.get(pe_offset..pe_offset + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    // COFF header
+    let section_count = read_u16(data, pe_offset + 6)? as usize;
+    let optional_header_size = read_u16(data, pe_offset + 20)? as usize;
+    let optional_header_offset = pe_offset + 24;
+
+    // Data directories are at a different offset for 32 and 64 bit binaries
+    let data_directories_offset = match read_u16(data, optional_header_offset)? {
+        0x10b => optional_header_offset + 96,
+        0x20b => optional_header_offset + 112,
+        _ => return None,
+    };
+    // Resource table is the third data directory
+    let resource_rva = read_u32(data, data_directories_offset + 2 * 8)?;
+    if resource_rva == 0 {
+        return None;
+    }
+
+    // Sections are needed to map virtual addresses to offsets in the file
+    let sections_offset = optional_header_offset + optional_header_size;
+    let rva_to_offset = |rva: u32| -> Option<usize> {
+        (0..section_count).find_map(|i| {
+            let section = sections_offset + i * 40;
+            let virtual_size = read_u32(data, section + 8)?;
+            let virtual_address = read_u32(data, section + 12)?;
+            let raw_size = read_u32(data, section + 16)?;
+            let raw_offset = read_u32(data, section + 20)?;
+            let offset = rva.checked_sub(virtual_address)?;
+            if offset < virtual_size.max(raw_size) {
+                Some(offset as usize + raw_offset as usize)
+            } else {
+                None
+            }
+        })
+    };
+    let resource_offset = rva_to_offset(resource_rva)?;
+
+    // Resources are stored in a tree of type, name and language
+    // Entries point either to a subdirectory, marked by the high bit, or to the data
+    let find_entry = |directory_offset: usize, id: Option<u32>| -> Option<u32> {
+        let named_entries = read_u16(data, directory_offset + 12)? as usize;
+        let id_entries = read_u16(data, directory_offset + 14)? as usize;
+        (0..named_entries + id_entries).find_map(|i| {
+            let entry = directory_offset + 16 + i * 8;
+            let entry_id = read_u32(data, entry)?;
+            match id {
+                Some(id) if entry_id != id => None,
+                _ => read_u32(data, entry + 4),
+            }
+        })
+    };
+    let subdirectory_offset = |entry: u32| -> Option<usize> {
+        if entry & 0x8000_0000 != 0 {
+            Some(resource_offset + (entry & 0x7FFF_FFFF) as usize)
+        } else {
+            None
+        }
+    };
+
+    let names_offset = subdirectory_offset(find_entry(resource_offset, Some(resource_type))?)?;
+    let languages_offset = subdirectory_offset(find_entry(names_offset, None)?)?;
+    let data_entry = find_entry(languages_offset, None)?;
+    if data_entry & 0x8000_0000 != 0 {
+        return None;
+    }
+
+    let data_entry_offset = resource_offset + data_entry as usize;
+    let data_offset = rva_to_offset(read_u32(data, data_entry_offset)?)?;
+    let data_size = read_u32(data, data_entry_offset + 4)? as usize;
+    data.get(data_offset..data_offset + data_size)
+}
+
+/// Reads the file version from the version resource of the given executable or DLL
+pub fn read_pe_file_version(path: &Path) -> Option<String> {
+    let data = std::fs::read(path).ok()?;
+    let version = parse_version_info(find_resource(&data, RT_VERSION)?)?;
+
+    // Northstar only uses three version components
+    Some(match version {
+        [major, minor, patch, 0] => format!("{major}.{minor}.{patch}"),
+        [major, minor, patch, build] => format!("{major}.{minor}.{patch}.{build}"),
+    })
+}
+
+/// Reduces version numbers like `1.24.0-rc1` or `1.24.0.0` to their numeric release
+pub fn normalize_version(version: &str) -> Vec<u32> {
+    version
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .take(3)
+        .map(|part| part.parse().unwrap_or_default())
+        .collect()
+}
+
+/// Returns launcher, DLL and core mod versions of the Northstar install of the profile separately
+#[tauri::command]
+pub fn get_northstar_component_versions(game_install: GameInstall) -> NorthstarComponentVersions {
+    let game_path = Path::new(&game_install.game_path);
+
+    let launcher_version = read_pe_file_version(&game_path.join("NorthstarLauncher.exe"));
+
+    // Older releases placed the DLL next to the launcher
+    let dll_version = [
+        game_path.join(&game_install.profile).join(NORTHSTAR_DLL),
+        game_path.join(NORTHSTAR_DLL),
+    ]
+    .iter()
+    .find(|path| path.exists())
+    .and_then(|path| read_pe_file_version(path.as_path()));
+
+    let core_mod_versions: Vec<CoreModVersion> = CORE_MODS
+        .iter()
+        .map(|core_mod| CoreModVersion {
+            name: core_mod.to_string(),
+            version: check_mod_version_number(&format!(
+                "{}/{}/mods/{}",
+                game_install.game_path, game_install.profile, core_mod
+            ))
+            .ok(),
+        })
+        .collect();
+
+    let mut versions = vec![&launcher_version, &dll_version];
+    versions.extend(core_mod_versions.iter().map(|core_mod| &core_mod.version));
+    let mismatch = versions
+        .iter()
+        .any(|version| match (version, &launcher_version) {
+            (Some(version), Some(launcher_version)) => {
+                normalize_version(version) != normalize_version(launcher_version)
+            }
+            // A missing part is a mismatch as well
+            _ => true,
+        });
+
+    NorthstarComponentVersions {
+        launcher_version,
+        dll_version,
+        core_mod_versions,
+        mismatch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version_info() {
+        let mut data = vec![0u8; 6];
+        data.extend(
+            "VS_VERSION_INFO\0"
+                .encode_utf16()
+                .flat_map(|character| character.to_le_bytes()),
+        );
+        data.extend([0, 0]); // Padding
+        data.extend(VS_FIXEDFILEINFO_SIGNATURE.to_le_bytes());
+        data.extend(0x0001_0000u32.to_le_bytes()); // Structure version
+        data.extend(((1u32 << 16) | 24).to_le_bytes());
+        data.extend((2u32 << 16).to_le_bytes());
+
+        assert_eq!(parse_version_info(&data), Some([1, 24, 2, 0]));
+        assert_eq!(parse_version_info(&data[..20]), None);
+    }
+
+    #[test]
+    fn test_normalize_version() {
+        assert_eq!(normalize_version("1.24.0-rc1"), vec![1, 24, 0]);
+        assert_eq!(normalize_version("1.24.0.0"), vec![1, 24, 0]);
+        assert_ne!(normalize_version("1.24.1"), normalize_version("1.24.0"));
+    }
+}
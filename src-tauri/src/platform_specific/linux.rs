@@ -1,5 +1,7 @@
 // Linux specific code
 
+use crate::constants::DEEP_LINK_SCHEME;
+
 fn get_proton_dir() -> Result<String, String> {
     let steam_dir = match steamlocate::SteamDir::locate() {
         Ok(result) => result,
@@ -96,3 +98,61 @@ pub fn get_local_ns_proton_version() -> Result<String, String> {
 
     Err("Northstar Proton is not installed".to_string())
 }
+
+/// Name of the desktop entry FlightCore registers for `ror2mm://` links
+fn get_deep_link_desktop_file_name() -> String {
+    format!("flightcore-{}.desktop", DEEP_LINK_SCHEME)
+}
+
+/// Checks whether the desktop entry of FlightCore is the default for `ror2mm://` links
+pub fn is_deep_link_handler_registered() -> bool {
+    match std::process::Command::new("xdg-mime")
+        .args([
+            "query",
+            "default",
+            &format!("x-scheme-handler/{}", DEEP_LINK_SCHEME),
+        ])
+        .output()
+    {
+        Ok(output) => {
+            String::from_utf8_lossy(&output.stdout).trim() == get_deep_link_desktop_file_name()
+        }
+        Err(err) => {
+            log::warn!("Failed running xdg-mime: {err}");
+            false
+        }
+    }
+}
+
+/// Registers FlightCore for `ror2mm://` links via a desktop entry
+pub fn register_deep_link_handler() -> Result<(), String> {
+    let exe_path = std::env::current_exe().map_err(|err| err.to_string())?;
+    let applications_dir = match dirs::data_dir() {
+        Some(path) => path.join("applications"),
+        None => return Err("Unable to find data directory".to_string()),
+    };
+    let desktop_file_name = get_deep_link_desktop_file_name();
+
+    let desktop_entry = format!(
+        "[Desktop Entry]\nType=Application\nName=FlightCore\nExec=\"{}\" %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        exe_path.display(),
+        DEEP_LINK_SCHEME
+    );
+
+    std::fs::create_dir_all(&applications_dir).map_err(|err| err.to_string())?;
+    std::fs::write(applications_dir.join(&desktop_file_name), desktop_entry)
+        .map_err(|err| err.to_string())?;
+
+    match std::process::Command::new("xdg-mime")
+        .args([
+            "default",
+            &desktop_file_name,
+            &format!("x-scheme-handler/{}", DEEP_LINK_SCHEME),
+        ])
+        .status()
+    {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("xdg-mime exited with {}", status)),
+        Err(err) => Err(format!("Failed running xdg-mime: {}", err)),
+    }
+}
@@ -10,6 +10,56 @@ pub fn get_host_os() -> String {
     std::env::consts::OS.to_string()
 }
 
+/// Registers FlightCore as the handler for Thunderstore "Install with Mod Manager" links
+pub fn register_deep_link_handler() -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    return linux::register_deep_link_handler();
+
+    #[cfg(target_os = "windows")]
+    windows::register_deep_link_handler()
+}
+
+/// Checks whether FlightCore is the current handler for Thunderstore "Install with Mod Manager" links
+pub fn is_deep_link_handler_registered() -> bool {
+    #[cfg(target_os = "linux")]
+    return linux::is_deep_link_handler_registered();
+
+    #[cfg(target_os = "windows")]
+    windows::is_deep_link_handler_registered()
+}
+
+/// Returns whether FlightCore handles Thunderstore "Install with Mod Manager" links
+#[tauri::command]
+pub fn get_deep_link_handler_registered() -> bool {
+    is_deep_link_handler_registered()
+}
+
+/// Makes FlightCore handle Thunderstore "Install with Mod Manager" links
+/// Only called once the user opted in as this takes the links over from other mod managers
+#[tauri::command]
+pub async fn register_deep_link_handler_wrapper() -> Result<(), String> {
+    register_deep_link_handler()
+}
+
+/// Makes command line output visible in the terminal FlightCore was started from
+pub fn attach_to_parent_console() {
+    #[cfg(target_os = "windows")]
+    windows::attach_to_parent_console();
+}
+
+/// Creates a symbolic link at `link` pointing to the folder `original`
+/// On Windows this requires developer mode or administrator rights
+pub fn create_dir_symlink(
+    original: &std::path::Path,
+    link: &std::path::Path,
+) -> std::io::Result<()> {
+    #[cfg(target_os = "linux")]
+    return std::os::unix::fs::symlink(original, link);
+
+    #[cfg(target_os = "windows")]
+    std::os::windows::fs::symlink_dir(original, link)
+}
+
 /// On Linux attempts to install NorthstarProton
 /// On Windows simply returns an error message
 #[tauri::command]
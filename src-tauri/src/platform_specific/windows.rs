@@ -3,7 +3,12 @@ use anyhow::{anyhow, Result};
 use std::net::Ipv4Addr;
 
 #[cfg(target_os = "windows")]
-use winreg::{enums::HKEY_LOCAL_MACHINE, RegKey};
+use winreg::{
+    enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE},
+    RegKey,
+};
+
+use crate::constants::DEEP_LINK_SCHEME;
 
 use crate::repair_and_verify::check_is_valid_game_path;
 
@@ -34,6 +39,65 @@ pub fn origin_install_location_detection() -> Result<String, anyhow::Error> {
     Err(anyhow!("No Origin / EA App install path found"))
 }
 
+/// Checks whether the `ror2mm://` link handler in the registry of the current user is FlightCore
+pub fn is_deep_link_handler_registered() -> bool {
+    let Some(exe_name) = std::env::current_exe().ok().and_then(|path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+    }) else {
+        return false;
+    };
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let command: String = match hkcu
+        .open_subkey(format!(
+            "Software\\Classes\\{}\\shell\\open\\command",
+            DEEP_LINK_SCHEME
+        ))
+        .and_then(|key| key.get_value(""))
+    {
+        Ok(command) => command,
+        Err(_) => return false,
+    };
+
+    // Compare by executable name so FlightCore being moved still counts as registered
+    command.to_lowercase().contains(&exe_name)
+}
+
+/// Attaches to the console of the process FlightCore was started from, if any
+/// Release builds are GUI applications which otherwise have no console to print to
+pub fn attach_to_parent_console() {
+    // Fails if there is no parent console, in which case output is simply discarded as before
+    unsafe {
+        winapi::um::wincon::AttachConsole(winapi::um::wincon::ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Registers FlightCore for `ror2mm://` links in the registry of the current user
+pub fn register_deep_link_handler() -> Result<(), String> {
+    let exe_path = std::env::current_exe().map_err(|err| err.to_string())?;
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let (scheme_key, _) = hkcu
+        .create_subkey(format!("Software\\Classes\\{}", DEEP_LINK_SCHEME))
+        .map_err(|err| err.to_string())?;
+    scheme_key
+        .set_value("", &format!("URL:{} Protocol", DEEP_LINK_SCHEME))
+        .map_err(|err| err.to_string())?;
+    scheme_key
+        .set_value("URL Protocol", &"")
+        .map_err(|err| err.to_string())?;
+
+    let (command_key, _) = scheme_key
+        .create_subkey("shell\\open\\command")
+        .map_err(|err| err.to_string())?;
+    command_key
+        .set_value("", &format!("\"{}\" \"%1\"", exe_path.display()))
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
 /// Check whether the current device might be behind a CGNAT
 pub async fn check_cgnat() -> Result<String, String> {
     // Use external service to grap IP
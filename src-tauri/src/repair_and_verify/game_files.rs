@@ -0,0 +1,327 @@
+//! Verifying the vanilla Titanfall 2 files against a known-good manifest
+//!
+//! The manifest lists path, size and hash of every game file. It can be generated from a clean
+//! install using `--generate-game-manifest <game path>`.
+use crate::northstar::uninstall::get_recorded_files;
+use crate::util::{get_relative_files_recursively, sha256_file};
+use crate::{GameInstall, InstallType};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// Expected size and SHA-256 hash of a game file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameFileEntry {
+    size: u64,
+    sha256: String,
+}
+
+/// Known-good state of a Titanfall 2 install
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GameFileManifest {
+    /// Maps path relative to the game folder to the expected file
+    files: BTreeMap<String, GameFileEntry>,
+}
+
+/// Result of comparing the game files against the manifest
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct GameFileVerificationResult {
+    missing_files: Vec<String>,
+    corrupt_files: Vec<String>,
+    /// Files in game folders that are not part of the game nor installed by Northstar
+    unexpected_files: Vec<String>,
+    /// How to get the store the game was installed from to repair it
+    repair_instructions: String,
+    /// Link opening the store's repair function directly, if there is one
+    repair_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+struct GameFileVerificationProgress {
+    checked: u32,
+    total: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileStatus {
+    Valid,
+    Missing,
+    Corrupt,
+}
+
+fn load_manifest(manifest_path: &str) -> Result<GameFileManifest> {
+    let manifest: GameFileManifest =
+        serde_json::from_str(&std::fs::read_to_string(manifest_path)?)?;
+    if manifest.files.is_empty() {
+        return Err(anyhow!(
+            "Manifest at {manifest_path} doesn't list any files"
+        ));
+    }
+    Ok(manifest)
+}
+
+fn check_file(path: &Path, expected: &GameFileEntry) -> FileStatus {
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return FileStatus::Missing,
+        Err(_) => return FileStatus::Corrupt,
+    };
+
+    // Comparing sizes first saves hashing files that are obviously wrong
+    if metadata.len() != expected.size {
+        return FileStatus::Corrupt;
+    }
+    match sha256_file(path) {
+        Ok(hash) if hash.eq_ignore_ascii_case(&expected.sha256) => FileStatus::Valid,
+        _ => FileStatus::Corrupt,
+    }
+}
+
+/// Checks all files of the manifest on multiple threads
+/// `on_progress` is called with the number of checked files after each file
+fn check_files(
+    game_path: &Path,
+    manifest: &GameFileManifest,
+    mut on_progress: impl FnMut(usize, usize),
+) -> BTreeMap<String, FileStatus> {
+    let entries: Vec<(&String, &GameFileEntry)> = manifest.files.iter().collect();
+    let next_entry = AtomicUsize::new(0);
+    let thread_count = std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(4);
+
+    let mut results = BTreeMap::new();
+    std::thread::scope(|scope| {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for _ in 0..thread_count {
+            let sender = sender.clone();
+            let (entries, next_entry) = (&entries, &next_entry);
+            scope.spawn(move || {
+                while let Some((path, expected)) =
+                    entries.get(next_entry.fetch_add(1, Ordering::Relaxed))
+                {
+                    let status = check_file(&game_path.join(path), expected);
+                    if sender.send((path.to_string(), status)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        // Only the workers hold senders now, so receiving ends once all of them are done
+        drop(sender);
+
+        for (path, status) in receiver {
+            results.insert(path, status);
+            on_progress(results.len(), entries.len());
+        }
+    });
+    results
+}
+
+/// Finds files next to game files that are neither part of the game nor of Northstar
+fn find_unexpected_files(game_install: &GameInstall, manifest: &GameFileManifest) -> Vec<String> {
+    let game_path = Path::new(&game_install.game_path);
+    let northstar_files: BTreeSet<String> = get_recorded_files(game_install).into_iter().collect();
+
+    // Only folders containing game files are checked, this skips profiles and other additions
+    let game_directories: BTreeSet<&str> = manifest
+        .files
+        .keys()
+        .map(|path| path.rsplit_once('/').map_or("", |(directory, _)| directory))
+        .collect();
+
+    let mut unexpected_files = Vec::new();
+    for directory in game_directories {
+        let Ok(entries) = std::fs::read_dir(game_path.join(directory)) else {
+            continue;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                continue;
+            }
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let path = match directory {
+                "" => file_name.clone(),
+                directory => format!("{directory}/{file_name}"),
+            };
+            if !manifest.files.contains_key(&path)
+                && !northstar_files.contains(&path)
+                && !file_name.starts_with("flightcore-")
+            {
+                unexpected_files.push(path);
+            }
+        }
+    }
+    unexpected_files
+}
+
+/// Describes how to repair the game using the store it was installed from
+fn get_repair_instructions(install_type: &InstallType) -> (String, Option<String>) {
+    match install_type {
+        InstallType::STEAM => (
+            "In Steam, right-click Titanfall 2, select Properties > Installed Files and click \"Verify integrity of game files\"".to_string(),
+            Some(format!("steam://validate/{}", thermite::TITANFALL2_STEAM_ID)),
+        ),
+        InstallType::ORIGIN => (
+            "In Origin, right-click Titanfall 2 in your game library and select \"Repair\"".to_string(),
+            None,
+        ),
+        InstallType::EAPLAY => (
+            "In the EA app, open Titanfall 2 in your library, click the three dots and select \"Repair\"".to_string(),
+            None,
+        ),
+        InstallType::UNKNOWN => (
+            "Use the repair function of the store you installed Titanfall 2 from or reinstall the game".to_string(),
+            None,
+        ),
+    }
+}
+
+/// Verifies the game files, emitting progress events to the window
+pub fn verify(
+    window: &tauri::Window,
+    game_install: &GameInstall,
+    manifest_path: &str,
+) -> Result<GameFileVerificationResult> {
+    let manifest = load_manifest(manifest_path)?;
+    log::info!(
+        "Verifying {} game files in {}",
+        manifest.files.len(),
+        game_install.game_path
+    );
+
+    let mut last_emit = Instant::now();
+    let results = check_files(
+        Path::new(&game_install.game_path),
+        &manifest,
+        |checked, total| {
+            // Only emit a signal once every 100ms, or when done
+            if checked == total || last_emit.elapsed() >= Duration::from_millis(100) {
+                window
+                    .emit(
+                        "verify-game-files-progress",
+                        GameFileVerificationProgress {
+                            checked: checked as u32,
+                            total: total as u32,
+                        },
+                    )
+                    .unwrap();
+                last_emit = Instant::now();
+            }
+        },
+    );
+
+    let files_with_status = |wanted: FileStatus| -> Vec<String> {
+        results
+            .iter()
+            .filter(|(_, status)| **status == wanted)
+            .map(|(path, _)| path.clone())
+            .collect()
+    };
+    let (repair_instructions, repair_url) = get_repair_instructions(&game_install.install_type);
+
+    Ok(GameFileVerificationResult {
+        missing_files: files_with_status(FileStatus::Missing),
+        corrupt_files: files_with_status(FileStatus::Corrupt),
+        unexpected_files: find_unexpected_files(game_install, &manifest),
+        repair_instructions,
+        repair_url,
+    })
+}
+
+/// Creates a manifest from the files of a clean game install
+pub fn generate_manifest(game_path: &Path) -> Result<GameFileManifest> {
+    let mut files = BTreeMap::new();
+    for relative_path in get_relative_files_recursively(game_path)? {
+        let path = game_path.join(&relative_path);
+        // Always use forward slashes so manifests are the same across platforms
+        let relative_path = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.insert(
+            relative_path,
+            GameFileEntry {
+                size: std::fs::metadata(&path)?.len(),
+                sha256: sha256_file(&path)?,
+            },
+        );
+    }
+    Ok(GameFileManifest { files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::create_test_directory;
+
+    fn entry_for(path: &Path) -> GameFileEntry {
+        GameFileEntry {
+            size: std::fs::metadata(path).unwrap().len(),
+            sha256: sha256_file(path).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_check_file() {
+        let temp_directory = create_test_directory();
+        let folder = temp_directory.path();
+        let path = folder.join("Titanfall2.exe");
+        std::fs::write(&path, "game").unwrap();
+        let expected = entry_for(&path);
+
+        assert_eq!(check_file(&path, &expected), FileStatus::Valid);
+
+        // Same size but different content
+        std::fs::write(&path, "gamf").unwrap();
+        assert_eq!(check_file(&path, &expected), FileStatus::Corrupt);
+
+        std::fs::write(&path, "truncated").unwrap();
+        assert_eq!(check_file(&path, &expected), FileStatus::Corrupt);
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(check_file(&path, &expected), FileStatus::Missing);
+    }
+
+    #[test]
+    fn test_find_unexpected_files() {
+        let temp_directory = create_test_directory();
+        let folder = temp_directory.path();
+        std::fs::create_dir_all(folder.join("bin/x64_retail")).unwrap();
+        std::fs::create_dir_all(folder.join("R2Northstar/mods")).unwrap();
+        for path in [
+            "Titanfall2.exe",
+            "bin/x64_retail/engine.dll",
+            "bin/x64_retail/injected.dll",
+            "flightcore-northstar-manifest.json",
+            "R2Northstar/mods/mod.json",
+        ] {
+            std::fs::write(folder.join(path), path).unwrap();
+        }
+
+        let manifest = GameFileManifest {
+            files: ["Titanfall2.exe", "bin/x64_retail/engine.dll"]
+                .iter()
+                .map(|path| (path.to_string(), entry_for(&folder.join(path))))
+                .collect(),
+        };
+        let game_install = GameInstall {
+            game_path: folder.to_string_lossy().to_string(),
+            profile: "R2Northstar".to_string(),
+            install_type: InstallType::UNKNOWN,
+        };
+
+        // FlightCore's own files and folders without game files are skipped
+        assert_eq!(
+            find_unexpected_files(&game_install, &manifest),
+            vec!["bin/x64_retail/injected.dll"]
+        );
+    }
+}
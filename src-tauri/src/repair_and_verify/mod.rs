@@ -2,6 +2,8 @@ use crate::mod_management::{get_enabled_mods, rebuild_enabled_mods_json, set_mod
 /// Contains various functions to repair common issues and verifying installation
 use crate::{constants::CORE_MODS, GameInstall};
 
+pub mod game_files;
+
 /// Checks if is valid Titanfall2 install based on certain conditions
 #[tauri::command]
 pub async fn verify_install_location(game_path: String) -> bool {
@@ -27,11 +29,28 @@ pub fn check_is_valid_game_path(game_install_path: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Verifies Titanfall2 game files
+/// Verifies Titanfall2 game files against the manifest at `manifest_path`
 #[tauri::command]
-pub fn verify_game_files(game_install: GameInstall) -> Result<String, String> {
-    dbg!(game_install);
-    Err("TODO, not yet implemented".to_string())
+pub async fn verify_game_files(
+    window: tauri::Window,
+    game_install: GameInstall,
+    manifest_path: String,
+) -> Result<game_files::GameFileVerificationResult, String> {
+    // Hashing the whole game takes a while, so do it off the main thread
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        game_files::verify(&window, &game_install, &manifest_path)
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+
+    match result {
+        Ok(result) => Ok(result),
+        Err(err) => {
+            let error_message = format!("Failed verifying game files: {err}");
+            log::error!("{error_message}");
+            Err(error_message)
+        }
+    }
 }
 
 /// Disables all mods except core ones
@@ -1,9 +1,12 @@
 //! For interacting with Thunderstore API
-use crate::constants::{APP_USER_AGENT, BLACKLISTED_MODS};
+use crate::constants::APP_USER_AGENT;
+use crate::mod_management::install_rules::get_install_rules;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use ts_rs::TS;
 
+pub mod upload;
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct ThunderstoreMod {
@@ -76,10 +79,14 @@ pub async fn query_thunderstore_packages_api() -> Result<Vec<ThunderstoreMod>, S
     };
 
     // Remove some mods from listing
-    let to_remove_set: HashSet<&str> = BLACKLISTED_MODS.iter().copied().collect();
+    let to_remove_set: HashSet<String> = get_install_rules()
+        .into_iter()
+        .filter(|rule| rule.hide_from_listing)
+        .map(|rule| rule.package)
+        .collect();
     let filtered_packages = parsed_json
         .into_iter()
-        .filter(|package| !to_remove_set.contains(&package.full_name.as_ref()))
+        .filter(|package| !to_remove_set.contains(&package.full_name))
         .collect::<Vec<ThunderstoreMod>>();
 
     Ok(filtered_packages)
@@ -0,0 +1,403 @@
+//! Publishing packages via Thunderstore's upload API
+//!
+//! Uploading happens in three steps: the zip is uploaded in parts to URLs handed out by
+//! Thunderstore, the upload is marked as finished, and finally submitted as a package version.
+use crate::constants::{APP_USER_AGENT, THUNDERSTORE_BASE_URL};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use ts_rs::TS;
+
+/// Where and how to publish a package
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct PackageUploadOptions {
+    /// Thunderstore team the package is published under
+    team: String,
+    communities: Vec<String>,
+    categories: Vec<String>,
+    has_nsfw_content: bool,
+    /// Defaults to Thunderstore if not set
+    base_url: Option<String>,
+}
+
+/// Outcome of publishing a package
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct PackageUploadResult {
+    /// Full name of the published version, set if successful
+    package_version: Option<String>,
+    /// Validation errors reported by Thunderstore
+    errors: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct UploadPart {
+    part_number: u32,
+    url: String,
+    offset: usize,
+    length: usize,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct UserMedia {
+    uuid: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct InitiateUploadResponse {
+    user_media: UserMedia,
+    upload_urls: Vec<UploadPart>,
+}
+
+enum ApiResponse {
+    Success(Value),
+    /// Thunderstore refused the request, e.g. due to an invalid package
+    Rejected(Vec<String>),
+}
+
+/// Turns Thunderstore's error responses, e.g. `{"field": ["message"]}`, into a flat list
+fn collect_errors(value: &Value, prefix: &str, errors: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                // Errors not tied to a specific field
+                let key = match key.as_str() {
+                    "non_field_errors" | "detail" | "__all__" => prefix.to_string(),
+                    key if prefix.is_empty() => key.to_string(),
+                    key => format!("{prefix}.{key}"),
+                };
+                collect_errors(value, &key, errors);
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                collect_errors(value, prefix, errors);
+            }
+        }
+        Value::String(message) if prefix.is_empty() => errors.push(message.clone()),
+        Value::String(message) => errors.push(format!("{prefix}: {message}")),
+        value => errors.push(format!("{prefix}: {value}")),
+    }
+}
+
+/// Sends a JSON request to the Thunderstore API and parses the response
+async fn send_json(
+    client: &reqwest::Client,
+    url: String,
+    token: &str,
+    body: Value,
+) -> Result<ApiResponse, anyhow::Error> {
+    let response = client
+        .post(url)
+        .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
+        .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send()
+        .await?;
+    let status = response.status();
+    let text = response.text().await?;
+
+    if status.is_client_error() {
+        let mut errors = Vec::new();
+        match serde_json::from_str::<Value>(&text) {
+            Ok(value) => collect_errors(&value, "", &mut errors),
+            Err(_) => errors.push(format!("{status}: {text}")),
+        }
+        return Ok(ApiResponse::Rejected(errors));
+    }
+    if !status.is_success() {
+        return Err(anyhow!("Thunderstore responded with {status}: {text}"));
+    }
+
+    Ok(ApiResponse::Success(serde_json::from_str(&text)?))
+}
+
+/// Uploads the parts of the zip and returns their ETags in the format Thunderstore expects
+async fn upload_parts(
+    client: &reqwest::Client,
+    data: &[u8],
+    parts: &[UploadPart],
+) -> Result<Vec<Value>, anyhow::Error> {
+    let mut uploaded_parts = Vec::new();
+    for part in parts {
+        let chunk = data
+            .get(part.offset..part.offset + part.length)
+            .ok_or_else(|| anyhow!("Part {} is out of bounds", part.part_number))?;
+
+        let response = client
+            .put(&part.url)
+            .header(reqwest::header::CONTENT_LENGTH, chunk.len())
+            .body(chunk.to_vec())
+            .send()
+            .await?
+            .error_for_status()?;
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .ok_or_else(|| anyhow!("No ETag returned for part {}", part.part_number))?;
+
+        uploaded_parts.push(json!({ "PartNumber": part.part_number, "ETag": etag }));
+    }
+    Ok(uploaded_parts)
+}
+
+async fn upload_package(
+    zip_path: &str,
+    token: &str,
+    options: &PackageUploadOptions,
+) -> Result<PackageUploadResult, anyhow::Error> {
+    let base_url = options
+        .base_url
+        .as_deref()
+        .unwrap_or(THUNDERSTORE_BASE_URL)
+        .trim_end_matches('/');
+    let data = std::fs::read(zip_path)?;
+    let file_name = std::path::Path::new(zip_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow!("Invalid zip path {zip_path}"))?;
+
+    let rejected = |errors| PackageUploadResult {
+        package_version: None,
+        errors,
+    };
+    let client = reqwest::Client::new();
+
+    log::info!("Initiating upload of {file_name} to {base_url}");
+    let initiated = match send_json(
+        &client,
+        format!("{base_url}/api/experimental/usermedia/initiate-upload/"),
+        token,
+        json!({ "filename": file_name, "file_size_bytes": data.len() }),
+    )
+    .await?
+    {
+        ApiResponse::Success(value) => serde_json::from_value::<InitiateUploadResponse>(value)?,
+        ApiResponse::Rejected(errors) => return Ok(rejected(errors)),
+    };
+    let upload_uuid = initiated.user_media.uuid;
+
+    let uploaded_parts = match upload_parts(&client, &data, &initiated.upload_urls).await {
+        Ok(uploaded_parts) => uploaded_parts,
+        Err(err) => {
+            // Let Thunderstore clean up the incomplete upload
+            if let Err(abort_err) = send_json(
+                &client,
+                format!("{base_url}/api/experimental/usermedia/{upload_uuid}/abort-upload/"),
+                token,
+                json!({}),
+            )
+            .await
+            {
+                log::warn!("Failed aborting upload: {abort_err}");
+            }
+            return Err(err);
+        }
+    };
+
+    if let ApiResponse::Rejected(errors) = send_json(
+        &client,
+        format!("{base_url}/api/experimental/usermedia/{upload_uuid}/finish-upload/"),
+        token,
+        json!({ "parts": uploaded_parts }),
+    )
+    .await?
+    {
+        return Ok(rejected(errors));
+    }
+
+    log::info!("Submitting upload {upload_uuid} as package");
+    let community_categories: serde_json::Map<String, Value> = options
+        .communities
+        .iter()
+        .map(|community| (community.clone(), json!(options.categories)))
+        .collect();
+    let submitted = match send_json(
+        &client,
+        format!("{base_url}/api/experimental/submission/submit/"),
+        token,
+        json!({
+            "author_name": options.team,
+            "communities": options.communities,
+            "community_categories": community_categories,
+            "has_nsfw_content": options.has_nsfw_content,
+            "upload_uuid": upload_uuid,
+        }),
+    )
+    .await?
+    {
+        ApiResponse::Success(value) => value,
+        ApiResponse::Rejected(errors) => return Ok(rejected(errors)),
+    };
+
+    let package_version = submitted
+        .pointer("/package_version/full_name")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    log::info!("Published {package_version:?}");
+
+    Ok(PackageUploadResult {
+        package_version,
+        errors: vec![],
+    })
+}
+
+/// Publishes the package zip to Thunderstore using the given service account token
+#[tauri::command]
+pub async fn upload_package_to_thunderstore(
+    zip_path: String,
+    token: String,
+    options: PackageUploadOptions,
+) -> Result<PackageUploadResult, String> {
+    match upload_package(&zip_path, &token, &options).await {
+        Ok(result) => Ok(result),
+        Err(err) => {
+            let warn_response = format!("Failed uploading package: {err}");
+            log::warn!("{warn_response}");
+            Err(warn_response)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{create_test_directory, serve_test_responses, test_http_response};
+
+    /// Writes a package zip with the given contents and returns its path
+    fn write_package_zip(directory: &std::path::Path, contents: &str) -> String {
+        let zip_path = directory.join("Example_Mod-1.0.0.zip");
+        std::fs::write(&zip_path, contents).unwrap();
+        zip_path.to_string_lossy().to_string()
+    }
+
+    /// Thunderstore's answer to initiating an upload consisting of a single part
+    fn initiate_response(part_url: &str, length: usize) -> String {
+        let body = json!({
+            "user_media": { "uuid": "upload-uuid" },
+            "upload_urls": [{ "part_number": 1, "url": part_url, "offset": 0, "length": length }],
+        });
+        test_http_response("201 Created", &body.to_string())
+    }
+
+    fn request_body(request: &str) -> Value {
+        let (_, body) = request.split_once("\r\n\r\n").unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    fn upload_options(base_url: String) -> PackageUploadOptions {
+        PackageUploadOptions {
+            team: "Example".to_string(),
+            communities: vec!["northstar".to_string()],
+            categories: vec!["mods".to_string()],
+            has_nsfw_content: false,
+            base_url: Some(base_url),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_package() {
+        let temp_directory = create_test_directory();
+        let zip_path = write_package_zip(temp_directory.path(), "hello");
+
+        let (part_url, part_server) = serve_test_responses(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"part-etag\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        ]);
+        let (base_url, api_server) = serve_test_responses(vec![
+            initiate_response(&format!("{part_url}/part-1"), 5),
+            test_http_response("200 OK", "{}"),
+            test_http_response(
+                "200 OK",
+                &json!({ "package_version": { "full_name": "Example-Example_Mod-1.0.0" } })
+                    .to_string(),
+            ),
+        ]);
+
+        let result = upload_package(&zip_path, "token", &upload_options(base_url))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.package_version.as_deref(),
+            Some("Example-Example_Mod-1.0.0")
+        );
+        assert!(result.errors.is_empty());
+
+        let part_requests = part_server.join().unwrap();
+        assert!(part_requests[0].starts_with("PUT /part-1 "));
+        assert!(part_requests[0].ends_with("\r\n\r\nhello"));
+
+        let api_requests = api_server.join().unwrap();
+        assert!(api_requests[0].starts_with("POST /api/experimental/usermedia/initiate-upload/ "));
+        assert!(api_requests[0]
+            .to_lowercase()
+            .contains("authorization: bearer token"));
+        assert_eq!(
+            request_body(&api_requests[0]),
+            json!({ "filename": "Example_Mod-1.0.0.zip", "file_size_bytes": 5 })
+        );
+        assert!(api_requests[1]
+            .starts_with("POST /api/experimental/usermedia/upload-uuid/finish-upload/ "));
+        assert_eq!(
+            request_body(&api_requests[1]),
+            json!({ "parts": [{ "PartNumber": 1, "ETag": "\"part-etag\"" }] })
+        );
+        assert!(api_requests[2].starts_with("POST /api/experimental/submission/submit/ "));
+        assert_eq!(
+            request_body(&api_requests[2]),
+            json!({
+                "author_name": "Example",
+                "communities": ["northstar"],
+                "community_categories": { "northstar": ["mods"] },
+                "has_nsfw_content": false,
+                "upload_uuid": "upload-uuid",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_package_aborts_failed_upload() {
+        let temp_directory = create_test_directory();
+        let zip_path = write_package_zip(temp_directory.path(), "hello");
+
+        let (part_url, part_server) =
+            serve_test_responses(vec![test_http_response("500 Internal Server Error", "")]);
+        let (base_url, api_server) = serve_test_responses(vec![
+            initiate_response(&format!("{part_url}/part-1"), 5),
+            test_http_response("200 OK", "{}"),
+        ]);
+
+        assert!(
+            upload_package(&zip_path, "token", &upload_options(base_url))
+                .await
+                .is_err()
+        );
+
+        part_server.join().unwrap();
+        let api_requests = api_server.join().unwrap();
+        assert_eq!(api_requests.len(), 2);
+        assert!(api_requests[1]
+            .starts_with("POST /api/experimental/usermedia/upload-uuid/abort-upload/ "));
+    }
+
+    #[test]
+    fn test_collect_errors() {
+        let response = json!({
+            "non_field_errors": ["Package version already exists"],
+            "community_categories": { "northstar": ["Invalid category"] },
+        });
+        let mut errors = Vec::new();
+        collect_errors(&response, "", &mut errors);
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "Package version already exists",
+                "community_categories.northstar: Invalid category",
+            ]
+        );
+    }
+}
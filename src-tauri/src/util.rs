@@ -1,7 +1,8 @@
 //! This module contains various utility/helper functions that do not fit into any other module
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use zip::ZipArchive;
 
 use crate::constants::{APP_USER_AGENT, MASTER_SERVER_URL, SERVER_BROWSER_ENDPOINT};
@@ -127,6 +128,152 @@ pub async fn kill_northstar() -> Result<(), String> {
     Ok(())
 }
 
+/// Why a downloaded archive doesn't match what was listed on Thunderstore
+#[derive(Debug)]
+pub enum DownloadVerificationError {
+    /// File is larger or smaller than listed, e.g. due to an interrupted download
+    SizeMismatch { expected: u64, actual: u64 },
+    /// Archive can't be read or a file inside it fails its checksum
+    CorruptArchive(String),
+}
+
+impl std::fmt::Display for DownloadVerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DownloadVerificationError::SizeMismatch { expected, actual } => write!(
+                f,
+                "Downloaded file has {actual} bytes but should have {expected} bytes"
+            ),
+            DownloadVerificationError::CorruptArchive(reason) => {
+                write!(f, "Downloaded archive is corrupted: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DownloadVerificationError {}
+
+/// Checks a downloaded zip file against its listed size and the checksums of the files inside
+/// Size check is skipped if `expected_size` is `0`
+pub fn verify_downloaded_archive(
+    file: &std::fs::File,
+    expected_size: u64,
+) -> Result<(), DownloadVerificationError> {
+    let actual = file
+        .metadata()
+        .map_err(|err| DownloadVerificationError::CorruptArchive(err.to_string()))?
+        .len();
+    if expected_size != 0 && actual != expected_size {
+        return Err(DownloadVerificationError::SizeMismatch {
+            expected: expected_size,
+            actual,
+        });
+    }
+
+    let mut archive = ZipArchive::new(file)
+        .map_err(|err| DownloadVerificationError::CorruptArchive(err.to_string()))?;
+    for i in 0..archive.len() {
+        // Reading an entry to the end checks its CRC32
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|err| DownloadVerificationError::CorruptArchive(err.to_string()))?;
+        std::io::copy(&mut entry, &mut std::io::sink()).map_err(|err| {
+            DownloadVerificationError::CorruptArchive(format!("{}: {}", entry.name(), err))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Finds offset and size of the central directory in the last bytes of a zip file
+fn find_zip_central_directory(tail: &[u8]) -> Option<(u64, u64)> {
+    // End of central directory record starts with its signature and is at least 22 bytes long
+    let record = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&i| tail[i..i + 4] == [0x50, 0x4b, 0x05, 0x06])?;
+    let size = u32::from_le_bytes(tail[record + 12..record + 16].try_into().ok()?);
+    let offset = u32::from_le_bytes(tail[record + 16..record + 20].try_into().ok()?);
+    Some((offset as u64, size as u64))
+}
+
+/// Name and size of a file inside a zip archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipEntryInfo {
+    pub name: String,
+    pub uncompressed_size: u64,
+}
+
+/// Reads names and sizes of all entries from the central directory of a zip file
+fn parse_zip_central_directory(data: &[u8]) -> Vec<ZipEntryInfo> {
+    let read_u16 = |offset: usize| -> Option<usize> {
+        Some(u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?) as usize)
+    };
+    let read_u32 = |offset: usize| -> Option<u64> {
+        Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as u64)
+    };
+
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    // Each entry starts with its signature followed by 46 bytes of fixed size fields
+    while data.get(offset..offset + 4) == Some(&[0x50, 0x4b, 0x01, 0x02]) {
+        let (Some(uncompressed_size), Some(name_length), Some(extra_length), Some(comment_length)) = (
+            read_u32(offset + 24),
+            read_u16(offset + 28),
+            read_u16(offset + 30),
+            read_u16(offset + 32),
+        ) else {
+            break;
+        };
+        let Some(name) = data.get(offset + 46..offset + 46 + name_length) else {
+            break;
+        };
+        entries.push(ZipEntryInfo {
+            name: String::from_utf8_lossy(name).to_string(),
+            uncompressed_size,
+        });
+        offset += 46 + name_length + extra_length + comment_length;
+    }
+    entries
+}
+
+/// Requests the given byte range of a remote file
+async fn fetch_range(client: &reqwest::Client, url: &str, range: String) -> Result<Vec<u8>> {
+    let response = client
+        .get(url)
+        .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
+        .header(reqwest::header::RANGE, range)
+        .send()
+        .await?
+        .error_for_status()?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(anyhow!("Server does not support range requests"));
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Lists the entries of a remote zip file without downloading all of it
+///
+/// Only the central directory at the end of the archive is requested using HTTP range requests
+pub async fn list_remote_zip_entries(url: &str) -> Result<Vec<ZipEntryInfo>> {
+    let client = reqwest::Client::new();
+
+    // End of central directory record is 22 bytes followed by a comment of up to 64KiB
+    let tail = fetch_range(&client, url, "bytes=-65557".to_string()).await?;
+    let (offset, size) = find_zip_central_directory(&tail)
+        .ok_or_else(|| anyhow!("No central directory found in {url}"))?;
+    if size == 0 {
+        return Ok(vec![]);
+    }
+
+    let central_directory = fetch_range(
+        &client,
+        url,
+        format!("bytes={}-{}", offset, offset + size - 1),
+    )
+    .await?;
+    Ok(parse_zip_central_directory(&central_directory))
+}
+
 /// Copied from `papa` source code and modified
 ///Extract N* zip file to target game path
 // fn extract(ctx: &Ctx, zip_file: File, target: &Path) -> Result<()> {
@@ -224,6 +371,55 @@ pub fn move_dir_all(
     Ok(())
 }
 
+/// Recursively collects the paths of all files inside a folder
+pub fn get_files_recursively(
+    dir: impl AsRef<std::path::Path>,
+) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let ty = entry.file_type()?;
+        if ty.is_dir() {
+            files.extend(get_files_recursively(entry.path())?);
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(files)
+}
+
+/// Recursively collects the paths of all files inside a folder, relative to that folder
+pub fn get_relative_files_recursively(
+    dir: impl AsRef<std::path::Path>,
+) -> std::io::Result<Vec<std::path::PathBuf>> {
+    Ok(get_files_recursively(&dir)?
+        .into_iter()
+        .filter_map(|path| {
+            path.strip_prefix(&dir)
+                .ok()
+                .map(std::path::Path::to_path_buf)
+        })
+        .collect())
+}
+
+/// Sums up the size of all files inside a folder in bytes
+pub fn get_folder_size(dir: impl AsRef<std::path::Path>) -> std::io::Result<u64> {
+    let mut size = 0;
+    for file in get_files_recursively(dir)? {
+        size += std::fs::metadata(file)?.len();
+    }
+    Ok(size)
+}
+
+/// Computes the SHA-256 hash of a file and returns it as a lowercase hex string
+pub fn sha256_file(path: impl AsRef<std::path::Path>) -> Result<String> {
+    let mut file = std::fs::File::open(&path)
+        .with_context(|| format!("Unable to open {}", path.as_ref().display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).context("Unable to read file for hashing")?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 /// Helps with converting release candidate numbers which are different on Thunderstore
 /// due to restrictions imposed by the platform
 pub fn convert_release_candidate_number(version_number: String) -> String {
@@ -264,6 +460,70 @@ pub fn convert_release_candidate_number(version_number: String) -> String {
     panic!();
 }
 
+/// Creates an empty folder for a single test that is deleted again once dropped,
+/// even if the test panics
+#[cfg(test)]
+pub fn create_test_directory() -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .prefix("flightcore-test-")
+        .tempdir()
+        .expect("Failed creating test directory")
+}
+
+/// Builds a raw HTTP response for [`serve_test_responses`]
+#[cfg(test)]
+pub fn test_http_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Stand-in server answering consecutive connections with the given raw HTTP responses
+/// Returns the base URL and a handle resolving to the raw requests received, including their body
+#[cfg(test)]
+pub fn serve_test_responses(
+    responses: Vec<String>,
+) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{BufRead, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            request.push_str(&String::from_utf8_lossy(&body));
+            requests.push(request);
+
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -321,4 +581,28 @@ mod tests {
 
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn test_parse_zip_central_directory() {
+        use std::io::Write;
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        zip.add_directory("mods/", options).unwrap();
+        zip.start_file("mods/Example.Mod/mod.json", options)
+            .unwrap();
+        zip.write_all(b"{}").unwrap();
+        let data = zip.finish().unwrap().into_inner();
+
+        let (offset, size) = find_zip_central_directory(&data).unwrap();
+        let central_directory = &data[offset as usize..(offset + size) as usize];
+        let entries = parse_zip_central_directory(central_directory);
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.uncompressed_size))
+                .collect::<Vec<_>>(),
+            vec![("mods/", 0), ("mods/Example.Mod/mod.json", 2)]
+        );
+    }
 }
@@ -19,7 +19,7 @@ import { NorthstarLaunchOptions } from "../../../src-tauri/bindings/NorthstarLau
 import { searchModule } from './modules/search';
 import { i18n } from '../main';
 import { pullRequestModule } from './modules/pull_requests';
-import { showErrorNotification, showNotification } from '../utils/ui';
+import { formatNorthstarInstallError, showErrorNotification, showNotification } from '../utils/ui';
 import { notificationsModule } from './modules/notifications';
 
 const persistentStore = new Store('flight-core-settings.json');
@@ -201,7 +201,7 @@ export const store = createStore<FlightCoreStore>({
                     })
                         .catch((error) => {
                             console.error(error);
-                            alert(error);
+                            alert(formatNorthstarInstallError(error));
                         });
 
                     _get_northstar_version_number(state);
@@ -218,7 +218,7 @@ export const store = createStore<FlightCoreStore>({
                     })
                         .catch((error) => {
                             console.error(error);
-                            alert(error);
+                            alert(formatNorthstarInstallError(error));
                         });
 
                     _get_northstar_version_number(state);
@@ -452,6 +452,9 @@ async function _initializeApp(state: any) {
 
         // Check installed Northstar version if found
         await _get_northstar_version_number(state);
+
+        // Install mod from deep link FlightCore might have been launched with
+        await _installPendingDeepLink(state);
     }
 
     await invoke<[number, number]>("get_server_player_count")
@@ -497,6 +500,37 @@ function _initializeListeners(state: any) {
         state.player_count = evt.payload.Ok[0];
         state.server_count = evt.payload.Ok[1];
     });
+
+    listen("deep-link-install-requested", function (evt: TauriEvent<any>) {
+        _installPendingDeepLink(state);
+    });
+}
+
+/**
+ * Installs the mod of a confirmed Thunderstore "Install with Mod Manager" link
+ * into the active profile, if there is one.
+ */
+async function _installPendingDeepLink(state: any) {
+    // Game install is needed to know where to install to
+    if (state.game_install.game_path === undefined) {
+        return;
+    }
+
+    const thunderstore_mod_string = await invoke<string | null>("take_pending_deep_link");
+    if (thunderstore_mod_string === null) {
+        return;
+    }
+
+    await invoke("install_mod_wrapper", { gameInstall: state.game_install, thunderstoreModString: thunderstore_mod_string })
+        .then((message) => {
+            showNotification(i18n.global.tc('mods.card.install_success', { modName: thunderstore_mod_string }));
+        })
+        .catch((error) => {
+            showErrorNotification(error);
+        })
+        .finally(() => {
+            store.commit('loadInstalledMods');
+        });
 }
 
 /**
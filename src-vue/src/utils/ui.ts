@@ -2,6 +2,8 @@ import { ElNotification, NotificationHandle } from "element-plus";
 import { appWindow, UserAttentionType } from '@tauri-apps/api/window';
 import { i18n } from "../main";
 import { store } from "../plugins/store";
+import { NorthstarInstallError } from "../../../src-tauri/bindings/NorthstarInstallError";
+import { PreflightError } from "../../../src-tauri/bindings/PreflightError";
 
 /**
  * Displays content to the user in the form of a notification appearing on screen bottom right.
@@ -36,4 +38,37 @@ function showErrorNotification(
     return showNotification(title, error, 'error');
 }
 
-export {showNotification, showErrorNotification};
+/**
+ * Turns a problem found before installing Northstar into a message for the user.
+ **/
+function formatPreflightError(error: PreflightError): string {
+    if (error === "NorthstarRunning") {
+        return i18n.global.tc('notification.preflight.northstar_running');
+    }
+    if ("InsufficientDiskSpace" in error) {
+        const { required, available } = error.InsufficientDiskSpace;
+        return i18n.global.t('notification.preflight.insufficient_disk_space', {
+            required: Math.floor(Number(required) / 1_000_000),
+            available: Math.floor(Number(available) / 1_000_000),
+        });
+    }
+    if ("NotWritable" in error) {
+        return i18n.global.t('notification.preflight.not_writable', { path: error.NotWritable.path });
+    }
+    if ("ReadOnlyFile" in error) {
+        return i18n.global.t('notification.preflight.read_only_file', { path: error.ReadOnlyFile.path });
+    }
+    return i18n.global.t('notification.preflight.locked_file', { path: error.LockedFile.path });
+}
+
+/**
+ * Turns the error of a failed Northstar install into a message for the user.
+ **/
+function formatNorthstarInstallError(error: NorthstarInstallError): string {
+    if ("Preflight" in error) {
+        return error.Preflight.map(formatPreflightError).join("\n");
+    }
+    return error.Other;
+}
+
+export {showNotification, showErrorNotification, formatNorthstarInstallError};